@@ -0,0 +1,352 @@
+//! Checks a day 24 circuit against the shape of a ripple-carry adder, `z = x + y`, and works out
+//! which gate outputs have to be swapped back to make it one.
+//!
+//! Bit 0 is a half adder and every other bit a full adder fed by the previous carry:
+//!
+//! ```text
+//! sum   = x XOR y        total = sum XOR carry  -> z
+//! gen   = x AND y        prop  = sum AND carry
+//!                        carry' = gen OR prop
+//! ```
+//!
+//! with the last carry driving the top bit of `z`.
+
+use std::fmt;
+
+use anyhow::{Context, Result, bail};
+use tracing::debug;
+
+use crate::circuit::{Circuit, Gate, WireId};
+
+/// Two gate outputs found to be swapped while checking one bit of the adder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repair {
+    pub bit: usize,
+    pub swap: (WireId, WireId),
+}
+
+/// Gate outputs change as swaps are repaired, so gates are tracked by index into
+/// `Circuit::gates` and their outputs looked up when needed.
+struct Checker {
+    circuit: Circuit,
+    repairs: Vec<Repair>,
+}
+
+impl Checker {
+    fn out(&self, gate: usize) -> WireId {
+        self.circuit.gates()[gate].out
+    }
+
+    fn name(&self, wire: WireId) -> &str {
+        self.circuit.name(wire)
+    }
+
+    fn swap(&mut self, bit: usize, a: WireId, b: WireId) -> Result<()> {
+        debug!(bit, a = self.name(a), b = self.name(b), "Swapped outputs");
+        self.circuit.swap_outputs(a, b)?;
+        self.repairs.push(Repair { bit, swap: (a, b) });
+        Ok(())
+    }
+
+    /// The `op` gate reading `a` and `b`. If no such gate exists but exactly one `op` gate reads
+    /// one of them, or only one of those drives `out`, its other input must have been swapped
+    /// with the wire we expected.
+    fn expect(
+        &mut self,
+        bit: usize,
+        op: Gate,
+        (a, b): (WireId, WireId),
+        out: Option<WireId>,
+    ) -> Result<usize> {
+        let gates = self.circuit.gates();
+        let reads = |g: usize, w: WireId| gates[g].left == w || gates[g].right == w;
+
+        let of_op: Vec<usize> = (0..gates.len()).filter(|g| gates[*g].op == op).collect();
+        if let Some(g) = of_op.iter().find(|g| reads(**g, a) && reads(**g, b)) {
+            return Ok(*g);
+        }
+
+        let mut candidates: Vec<usize> = of_op
+            .into_iter()
+            .filter(|g| reads(*g, a) || reads(*g, b))
+            .collect();
+        if candidates.len() > 1
+            && let Some(out) = out
+        {
+            candidates.retain(|g| gates[*g].out == out);
+        }
+        let [g] = candidates[..] else {
+            bail!(
+                "Bit {}: expected {:?} of {} and {}, found {} gates reading either",
+                bit,
+                op,
+                self.name(a),
+                self.name(b),
+                candidates.len()
+            );
+        };
+
+        let gate = &gates[g];
+        let (expected, kept) = if reads(g, a) { (b, a) } else { (a, b) };
+        let actual = if gate.left == kept {
+            gate.right
+        } else {
+            gate.left
+        };
+        self.swap(bit, expected, actual)?;
+        Ok(g)
+    }
+
+    /// Make `gate` drive `wire`, swapping if it drives something else.
+    fn drive(&mut self, bit: usize, gate: usize, wire: WireId) -> Result<()> {
+        let out = self.out(gate);
+        if out != wire {
+            self.swap(bit, out, wire)?;
+        }
+        Ok(())
+    }
+}
+
+/// Walk the adder from the least significant bit, swapping outputs back as soon as a bit doesn't
+/// match the expected shape. Fails if a bit is too far off for a single swap to explain.
+pub fn repair(circuit: &Circuit) -> Result<Vec<Repair>> {
+    let (xs, ys, zs) = (circuit.bus('x'), circuit.bus('y'), circuit.bus('z'));
+    if xs.is_empty() || xs.len() != ys.len() {
+        bail!("Expected x and y buses of the same width");
+    }
+    if zs.len() != xs.len() + 1 {
+        bail!("Expected a z bus one bit wider than x and y");
+    }
+
+    let mut checker = Checker {
+        circuit: circuit.clone(),
+        repairs: Vec::new(),
+    };
+
+    let mut carry: Option<usize> = None;
+    for bit in 0..xs.len() {
+        let inputs = (xs[bit], ys[bit]);
+        let sum = checker.expect(bit, Gate::Xor, inputs, None)?;
+        let generate = checker.expect(bit, Gate::And, inputs, None)?;
+
+        carry = Some(match carry {
+            None => {
+                checker.drive(bit, sum, zs[bit])?;
+                generate
+            }
+            Some(carry) => {
+                let inputs = (checker.out(sum), checker.out(carry));
+                let total = checker.expect(bit, Gate::Xor, inputs, Some(zs[bit]))?;
+                checker.drive(bit, total, zs[bit])?;
+
+                let inputs = (checker.out(sum), checker.out(carry));
+                let propagate = checker.expect(bit, Gate::And, inputs, None)?;
+                let inputs = (checker.out(generate), checker.out(propagate));
+                checker.expect(bit, Gate::Or, inputs, None)?
+            }
+        });
+    }
+
+    let carry = carry.context("Adder has no bits")?;
+    checker.drive(xs.len(), carry, zs[xs.len()])?;
+
+    Ok(checker.repairs)
+}
+
+/// An addition the circuit gets wrong.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub x: u64,
+    pub y: u64,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+impl Mismatch {
+    /// Lowest bit of `z` that came out wrong.
+    pub fn bit(&self) -> u32 {
+        (self.expected ^ self.actual).trailing_zeros()
+    }
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} + {} gave {} instead of {}, first wrong at z{:02}",
+            self.x,
+            self.y,
+            self.actual,
+            self.expected,
+            self.bit()
+        )
+    }
+}
+
+/// Seed for the random additions, fixed so that a failure can be reproduced.
+const SEED: u64 = 0x2024_1224;
+
+/// SplitMix64, enough to spread test inputs over every bit.
+fn next_random(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// The additions to try on an adder with `bits` wide inputs: zero, each bit alone and doubled,
+/// carries rippling from bit 0 up to each bit, all ones, then `samples` random pairs.
+fn cases(bits: usize, samples: usize) -> Vec<(u64, u64)> {
+    let mask = (1 << bits) - 1;
+
+    let mut cases = vec![(0, 0)];
+    for bit in 0..bits {
+        let one = 1 << bit;
+        cases.extend([(one, 0), (0, one), (one, one)]);
+    }
+    for bit in 1..=bits {
+        let below = (1 << bit) - 1;
+        cases.extend([(below, 1), (1, below)]);
+    }
+    cases.extend([(mask, 0), (0, mask), (mask, mask)]);
+
+    let mut state = SEED;
+    for _ in 0..samples {
+        let x = next_random(&mut state) & mask;
+        let y = next_random(&mut state) & mask;
+        cases.push((x, y));
+    }
+    cases
+}
+
+/// Apply `swaps` and check the circuit adds `x + y` on every case, returning the first one it
+/// gets wrong. Fails if the swaps don't leave a circuit that can be simulated at all.
+pub fn test_swaps(
+    circuit: &Circuit,
+    swaps: &[(WireId, WireId)],
+    samples: usize,
+) -> Result<Option<Mismatch>> {
+    let swapped = circuit.with_swaps(swaps)?;
+    let evaluator = swapped.evaluator()?;
+
+    let bits = swapped.bus('x').len().min(swapped.bus('y').len());
+    if bits >= 64 {
+        bail!("Can't test a {} bit adder, sums must fit in 64 bits", bits);
+    }
+    for (x, y) in cases(bits, samples) {
+        let actual = evaluator.add(x, y);
+        if actual != x + y {
+            return Ok(Some(Mismatch {
+                x,
+                y,
+                expected: x + y,
+                actual,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// A sound ripple-carry adder over `bits` bits, with the inputs all off.
+    pub fn adder(bits: usize) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        for bit in 0..bits {
+            lines.push(format!("x{:02}: 0", bit));
+            lines.push(format!("y{:02}: 0", bit));
+        }
+        lines.push(String::new());
+        lines.push("x00 XOR y00 -> z00".to_string());
+        lines.push("x00 AND y00 -> cry00".to_string());
+        for bit in 1..bits {
+            let carry_out = if bit + 1 == bits {
+                format!("z{:02}", bits)
+            } else {
+                format!("cry{:02}", bit)
+            };
+            lines.extend([
+                format!("x{0:02} XOR y{0:02} -> sum{0:02}", bit),
+                format!("y{0:02} AND x{0:02} -> gen{0:02}", bit),
+                format!("cry{1:02} XOR sum{0:02} -> z{0:02}", bit, bit - 1),
+                format!("sum{0:02} AND cry{1:02} -> prp{0:02}", bit, bit - 1),
+                format!("gen{0:02} OR prp{0:02} -> {1}", bit, carry_out),
+            ]);
+        }
+        lines.join("\n") + "\n"
+    }
+
+    fn swapped(circuit: &Circuit, pairs: &[(&str, &str)]) -> Circuit {
+        let swaps: Vec<(WireId, WireId)> = pairs
+            .iter()
+            .map(|(a, b)| (circuit.wire(a).unwrap(), circuit.wire(b).unwrap()))
+            .collect();
+        circuit.with_swaps(&swaps).unwrap()
+    }
+
+    fn names(circuit: &Circuit, repairs: &[Repair]) -> Vec<String> {
+        let mut names: Vec<String> = repairs
+            .iter()
+            .flat_map(|r| [r.swap.0, r.swap.1])
+            .map(|w| circuit.name(w).to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn sound_adder_needs_no_repairs() {
+        let circuit = Circuit::parse(&adder(12)).unwrap();
+        assert_eq!(repair(&circuit).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn finds_swapped_outputs() {
+        let circuit = Circuit::parse(&adder(12)).unwrap();
+        let broken = swapped(
+            &circuit,
+            &[
+                ("sum03", "gen03"),
+                ("z05", "cry05"),
+                ("z07", "prp07"),
+                ("z11", "sum11"),
+            ],
+        );
+        // sum11 now feeds the XOR driving it
+        assert!(broken.evaluator().is_err());
+
+        let repairs = repair(&broken).unwrap();
+        assert_eq!(
+            names(&broken, &repairs),
+            [
+                "cry05", "gen03", "prp07", "sum03", "sum11", "z05", "z07", "z11"
+            ]
+        );
+        assert_eq!(
+            repairs.iter().map(|r| r.bit).collect::<Vec<_>>(),
+            [3, 5, 7, 11]
+        );
+
+        // The sum lands on the next bit's carry, which two XORs read
+        let broken = swapped(&circuit, &[("z01", "z02"), ("sum09", "cry09")]);
+        let repairs = repair(&broken).unwrap();
+        assert_eq!(names(&broken, &repairs), ["cry09", "sum09", "z01", "z02"]);
+    }
+
+    #[test]
+    fn tests_repaired_circuits() {
+        let circuit = Circuit::parse(&adder(45)).unwrap();
+        assert_eq!(test_swaps(&circuit, &[], 1000).unwrap(), None);
+
+        let broken = swapped(&circuit, &[("sum03", "gen03")]);
+        let mismatch = test_swaps(&broken, &[], 1000).unwrap().unwrap();
+        assert_eq!((mismatch.x, mismatch.y, mismatch.bit()), (8, 0, 3));
+
+        let repairs = repair(&broken).unwrap();
+        let swaps: Vec<(WireId, WireId)> = repairs.iter().map(|r| r.swap).collect();
+        assert_eq!(test_swaps(&broken, &swaps, 1000).unwrap(), None);
+    }
+}
@@ -0,0 +1,233 @@
+//! Animations of the day 15 robot following its instructions, as ANSI coloured frames in the
+//! terminal or as an animated GIF. The robot and the boxes it pushed on each step stand out from
+//! the rest of the warehouse.
+
+use std::{
+    collections::HashSet,
+    fmt::Write as _,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Context, Result, bail};
+use gif::{Encoder, Frame, Repeat};
+
+use crate::util::{Direction, Position};
+use crate::warehouse::{RobotPlan, Tile, Warehouse};
+
+/// What a cell of a frame shows, which doubles as its index in the GIF palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Floor,
+    Wall,
+    Box,
+    Moved,
+    Robot,
+}
+
+/// RGB for each `Cell`, in order.
+const PALETTE: [u8; 15] = [
+    0x1e, 0x1e, 0x2e, // Floor
+    0x6c, 0x70, 0x86, // Wall
+    0x9c, 0x6b, 0x3c, // Box
+    0xfa, 0xb3, 0x87, // Moved
+    0xf9, 0xe2, 0xaf, // Robot
+];
+
+const ROBOT_ANSI: &str = "\x1b[1;33m";
+const MOVED_ANSI: &str = "\x1b[1;32m";
+const RESET_ANSI: &str = "\x1b[0m";
+
+/// The first `steps` instructions, failing if there aren't that many.
+fn first_steps(plan: &RobotPlan, steps: usize) -> Result<&[Direction]> {
+    if steps > plan.directions.len() {
+        bail!(
+            "Can't animate {} steps of {} instructions",
+            steps,
+            plan.directions.len()
+        );
+    }
+    Ok(&plan.directions[..steps])
+}
+
+/// Call `draw` with the warehouse as it stands, then again after each of `directions`.
+fn each_frame(
+    warehouse: &mut Warehouse,
+    directions: &[Direction],
+    mut draw: impl FnMut(&Warehouse) -> Result<()>,
+) -> Result<()> {
+    draw(warehouse)?;
+    for d in directions {
+        warehouse.step(d.clone());
+        draw(warehouse)?;
+    }
+    Ok(())
+}
+
+fn cells(warehouse: &Warehouse) -> impl Iterator<Item = (Position, Tile, Cell)> + '_ {
+    let moved: HashSet<Position> = warehouse.moved().into_iter().collect();
+    warehouse.map().iter().map(move |(pos, tile)| {
+        let cell = match tile {
+            _ if pos == warehouse.robot() => Cell::Robot,
+            _ if moved.contains(&pos) => Cell::Moved,
+            Tile::Wall => Cell::Wall,
+            Tile::Empty => Cell::Floor,
+            Tile::Box | Tile::BoxLeft | Tile::BoxRight => Cell::Box,
+        };
+        (pos, *tile, cell)
+    })
+}
+
+/// The warehouse as text, with the robot and the boxes it just pushed in colour.
+fn ansi_frame(warehouse: &Warehouse, steps: usize) -> String {
+    let mut out = String::new();
+    let last = warehouse.log().last();
+    let arrow = last.and_then(|m| m.direction.arrow()).unwrap_or(' ');
+    writeln!(
+        out,
+        "Step {}/{} {}  GPS {}",
+        warehouse.log().len(),
+        steps,
+        arrow,
+        warehouse.gps()
+    )
+    .unwrap();
+
+    for ((_, col), tile, cell) in cells(warehouse) {
+        if col == 0 && !out.ends_with('\n') {
+            out.push('\n');
+        }
+        match cell {
+            Cell::Robot => write!(out, "{}@{}", ROBOT_ANSI, RESET_ANSI),
+            Cell::Moved => write!(out, "{}{}{}", MOVED_ANSI, tile, RESET_ANSI),
+            _ => write!(out, "{}", tile),
+        }
+        .unwrap();
+    }
+    out.push('\n');
+    out
+}
+
+/// Draw each step in the terminal, `fps` times a second.
+pub fn play(
+    plan: &RobotPlan,
+    wide: bool,
+    steps: usize,
+    fps: f64,
+    out: &mut impl Write,
+) -> Result<()> {
+    if fps <= 0.0 {
+        bail!("Need a positive frame rate, not {}", fps);
+    }
+    let delay = Duration::from_secs_f64(1.0 / fps);
+
+    let directions = first_steps(plan, steps)?;
+    let mut warehouse = Warehouse::new(&plan.layout, wide)?;
+    each_frame(&mut warehouse, directions, |warehouse| {
+        // Home the cursor and clear the screen before each frame
+        write!(out, "\x1b[H\x1b[2J{}", ansi_frame(warehouse, steps))?;
+        out.flush()?;
+        thread::sleep(delay);
+        Ok(())
+    })
+}
+
+/// Write each step as a frame of an animated GIF, drawing every cell as a `scale` pixel square.
+pub fn write_gif(
+    plan: &RobotPlan,
+    wide: bool,
+    steps: usize,
+    fps: f64,
+    scale: usize,
+    path: &Path,
+) -> Result<()> {
+    if fps <= 0.0 || scale == 0 {
+        bail!("Need a positive frame rate and scale");
+    }
+    // GIF delays are in hundredths of a second
+    let delay = (100.0 / fps).round().clamp(1.0, u16::MAX as f64) as u16;
+
+    let directions = first_steps(plan, steps)?;
+    let mut warehouse = Warehouse::new(&plan.layout, wide)?;
+    let (rows, cols) = (
+        warehouse.map().rows() as usize,
+        warehouse.map().cols() as usize,
+    );
+    let (width, height) = (cols * scale, rows * scale);
+    let (Ok(w), Ok(h)) = (u16::try_from(width), u16::try_from(height)) else {
+        bail!("A {}x{} image is too big for a GIF", width, height);
+    };
+
+    let file = File::create(path).with_context(|| format!("Couldn't create {}", path.display()))?;
+    let mut encoder = Encoder::new(BufWriter::new(file), w, h, &PALETTE)?;
+    encoder.set_repeat(Repeat::Infinite)?;
+
+    each_frame(&mut warehouse, directions, |warehouse| {
+        let mut pixels = vec![0; width * height];
+        for ((row, col), _, cell) in cells(warehouse) {
+            for y in 0..scale {
+                let start = (row as usize * scale + y) * width + col as usize * scale;
+                pixels[start..start + scale].fill(cell as u8);
+            }
+        }
+        let mut frame = Frame::from_indexed_pixels(w, h, pixels, None);
+        frame.delay = delay;
+        encoder.write_frame(&frame)?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAN: &str = "#####\n#@O.#\n#####\n\n>>\n";
+
+    #[test]
+    fn highlights_the_robot_and_pushed_boxes() {
+        let plan = RobotPlan::parse(PLAN).unwrap();
+        let mut warehouse = Warehouse::new(&plan.layout, false).unwrap();
+        let mut text = Vec::new();
+        each_frame(&mut warehouse, first_steps(&plan, 2).unwrap(), |w| {
+            text.push(ansi_frame(w, 2));
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(text.len(), 3);
+        assert_eq!(
+            text[0],
+            "Step 0/2    GPS 102\n#####\n#\x1b[1;33m@\x1b[0mO.#\n#####\n"
+        );
+        assert_eq!(
+            text[1],
+            "Step 1/2 >  GPS 103\n#####\n#.\x1b[1;33m@\x1b[0m\x1b[1;32mO\x1b[0m#\n#####\n"
+        );
+        // Blocked, so nothing was pushed
+        assert!(!text[2].contains(MOVED_ANSI));
+
+        assert!(first_steps(&plan, 3).is_err());
+    }
+
+    #[test]
+    fn writes_gifs() {
+        let plan = RobotPlan::parse(PLAN).unwrap();
+        let path =
+            std::env::temp_dir().join(format!("advent24-warehouse-{}.gif", std::process::id()));
+        write_gif(&plan, true, 2, 10.0, 3, &path).unwrap();
+
+        let mut decoder = gif::DecodeOptions::new()
+            .read_info(File::open(&path).unwrap())
+            .unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (30, 9));
+        let mut count = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 10);
+            count += 1;
+        }
+        assert_eq!(count, 3);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
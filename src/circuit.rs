@@ -0,0 +1,360 @@
+//! Boolean gate circuits as used by day 24: a netlist of `AND`/`OR`/`XOR` gates between named
+//! wires, with numbered wires such as `x00`, `x01`... forming buses.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result, bail};
+use itertools::Itertools;
+
+/// Index of a wire in a `Circuit`.
+pub type WireId = usize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Gate {
+    And,
+    Or,
+    Xor,
+}
+
+impl Gate {
+    pub fn apply(&self, a: bool, b: bool) -> bool {
+        match self {
+            Gate::And => a & b,
+            Gate::Or => a | b,
+            Gate::Xor => a ^ b,
+        }
+    }
+}
+
+/// One gate, reading `left` and `right` and driving `out`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GateConfig {
+    pub left: WireId,
+    pub right: WireId,
+    pub op: Gate,
+    pub out: WireId,
+}
+
+#[derive(Clone, Debug)]
+pub struct Circuit {
+    names: Vec<String>,
+    ids: HashMap<String, WireId>,
+    /// Wires given a fixed value by the input, like `x00: 1`.
+    constants: Vec<(WireId, bool)>,
+    gates: Vec<GateConfig>,
+    /// For each wire, the index of the gate driving it.
+    drivers: Vec<Option<usize>>,
+    /// Bus name to its wires, least significant bit first.
+    buses: HashMap<char, Vec<WireId>>,
+}
+
+impl Circuit {
+    /// Parse `name: 0|1` constants, a blank line, then `a OP b -> out` gates.
+    pub fn parse(content: &str) -> Result<Circuit> {
+        let mut names: Vec<String> = Vec::new();
+        let mut ids: HashMap<String, WireId> = HashMap::new();
+        let mut intern = |name: &str| -> WireId {
+            *ids.entry(name.to_string()).or_insert_with(|| {
+                names.push(name.to_string());
+                names.len() - 1
+            })
+        };
+
+        let mut constants: Vec<(WireId, bool)> = Vec::new();
+        let mut gates: Vec<GateConfig> = Vec::new();
+
+        let (constant_lines, gate_lines) = content
+            .split_once("\n\n")
+            .context("Expected constants and gates separated by a blank line")?;
+
+        for line in constant_lines.lines() {
+            let (name, value) = line.split_once(": ").context("Bad constant line")?;
+            let value = match value.trim() {
+                "0" => false,
+                "1" => true,
+                _ => bail!("Bad constant value in {:?}", line),
+            };
+            constants.push((intern(name), value));
+        }
+
+        for line in gate_lines.lines().filter(|l| !l.is_empty()) {
+            let Some((left, op, right, "->", out)) = line.split_whitespace().collect_tuple() else {
+                bail!("Bad gate line {:?}", line);
+            };
+            let op = match op {
+                "AND" => Gate::And,
+                "OR" => Gate::Or,
+                "XOR" => Gate::Xor,
+                _ => bail!("Unexpected operation {}", op),
+            };
+            gates.push(GateConfig {
+                left: intern(left),
+                right: intern(right),
+                op,
+                out: intern(out),
+            });
+        }
+
+        let mut drivers: Vec<Option<usize>> = vec![None; names.len()];
+        for (i, gate) in gates.iter().enumerate() {
+            if drivers[gate.out].is_some() || constants.iter().any(|(w, _)| *w == gate.out) {
+                bail!("Wire {} is driven more than once", names[gate.out]);
+            }
+            drivers[gate.out] = Some(i);
+        }
+        for gate in gates.iter() {
+            for wire in [gate.left, gate.right] {
+                if drivers[wire].is_none() && !constants.iter().any(|(w, _)| *w == wire) {
+                    bail!("Wire {} is never driven", names[wire]);
+                }
+            }
+        }
+
+        let mut buses: HashMap<char, Vec<(u32, WireId)>> = HashMap::new();
+        for (id, name) in names.iter().enumerate() {
+            let mut chars = name.chars();
+            if let Some(bus) = chars.next()
+                && let Ok(bit) = chars.as_str().parse::<u32>()
+            {
+                buses.entry(bus).or_default().push((bit, id));
+            }
+        }
+        let buses = buses
+            .into_iter()
+            .map(|(bus, mut wires)| {
+                wires.sort();
+                (bus, wires.into_iter().map(|(_, id)| id).collect())
+            })
+            .collect();
+
+        Ok(Circuit {
+            names,
+            ids,
+            constants,
+            gates,
+            drivers,
+            buses,
+        })
+    }
+
+    pub fn wire_count(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn wire(&self, name: &str) -> Option<WireId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, wire: WireId) -> &str {
+        &self.names[wire]
+    }
+
+    pub fn gates(&self) -> &[GateConfig] {
+        &self.gates
+    }
+
+    /// Index of the gate driving `wire`, or `None` for a constant.
+    pub fn driver(&self, wire: WireId) -> Option<usize> {
+        self.drivers[wire]
+    }
+
+    /// Names of the buses, in order.
+    pub fn bus_names(&self) -> Vec<char> {
+        self.buses.keys().copied().sorted().collect()
+    }
+
+    /// Wires of a bus, least significant bit first. Empty if there is no such bus.
+    pub fn bus(&self, bus: char) -> &[WireId] {
+        self.buses.get(&bus).map_or(&[], |w| w.as_slice())
+    }
+
+    /// Swap the outputs of the gates driving `a` and `b`.
+    pub fn swap_outputs(&mut self, a: WireId, b: WireId) -> Result<()> {
+        let (Some(ga), Some(gb)) = (self.drivers[a], self.drivers[b]) else {
+            bail!("Only gate outputs can be swapped");
+        };
+        self.gates[ga].out = b;
+        self.gates[gb].out = a;
+        self.drivers.swap(a, b);
+        Ok(())
+    }
+
+    /// A copy of the circuit with each pair of outputs swapped.
+    pub fn with_swaps(&self, swaps: &[(WireId, WireId)]) -> Result<Circuit> {
+        let mut circuit = self.clone();
+        for (a, b) in swaps {
+            circuit.swap_outputs(*a, *b)?;
+        }
+        Ok(circuit)
+    }
+
+    /// Value of every wire before any gate has fired: the constants, everything else off.
+    pub fn initial_values(&self) -> Vec<bool> {
+        let mut values = vec![false; self.names.len()];
+        for (wire, value) in &self.constants {
+            values[*wire] = *value;
+        }
+        values
+    }
+
+    pub fn set_bus(&self, values: &mut [bool], bus: char, value: u64) {
+        for (bit, wire) in self.bus(bus).iter().enumerate() {
+            values[*wire] = (value >> bit) & 1 == 1;
+        }
+    }
+
+    pub fn read_bus(&self, values: &[bool], bus: char) -> u64 {
+        self.bus(bus)
+            .iter()
+            .enumerate()
+            .fold(0, |acc, (bit, wire)| acc | (values[*wire] as u64) << bit)
+    }
+
+    /// Sort the gates so each comes after the gates driving its inputs, failing with the wires
+    /// of a loop if there is one.
+    pub fn evaluator(&self) -> Result<Evaluator<'_>> {
+        let mut pending: Vec<usize> = self
+            .gates
+            .iter()
+            .map(|g| {
+                [g.left, g.right]
+                    .iter()
+                    .filter(|w| self.drivers[**w].is_some())
+                    .count()
+            })
+            .collect();
+        let mut readers: Vec<Vec<usize>> = vec![Vec::new(); self.names.len()];
+        for (i, gate) in self.gates.iter().enumerate() {
+            readers[gate.left].push(i);
+            if gate.right != gate.left {
+                readers[gate.right].push(i);
+            } else {
+                pending[i] = pending[i].min(1);
+            }
+        }
+
+        let mut ready: Vec<usize> = (0..self.gates.len()).filter(|i| pending[*i] == 0).collect();
+        let mut order: Vec<usize> = Vec::with_capacity(self.gates.len());
+        while let Some(i) = ready.pop() {
+            order.push(i);
+            for reader in &readers[self.gates[i].out] {
+                pending[*reader] -= 1;
+                if pending[*reader] == 0 {
+                    ready.push(*reader);
+                }
+            }
+        }
+
+        if order.len() < self.gates.len() {
+            let stuck = (0..self.gates.len())
+                .find(|i| pending[*i] > 0)
+                .expect("Some gate is stuck");
+            bail!(
+                "Circuit has a loop: {}",
+                self.find_loop(stuck, &pending).join(" -> ")
+            );
+        }
+
+        Ok(Evaluator {
+            circuit: self,
+            order,
+        })
+    }
+
+    /// Walk back from a gate that never became ready through its unready inputs until a wire
+    /// repeats. Every unready gate has an unready input, so this always ends in a loop.
+    fn find_loop(&self, start: usize, pending: &[usize]) -> Vec<String> {
+        let mut seen: HashMap<WireId, usize> = HashMap::new();
+        let mut path: Vec<WireId> = Vec::new();
+        let mut gate = start;
+        loop {
+            let wire = self.gates[gate].out;
+            if let Some(i) = seen.get(&wire) {
+                let mut cycle: Vec<String> = path[*i..]
+                    .iter()
+                    .rev()
+                    .map(|w| self.names[*w].clone())
+                    .collect();
+                cycle.push(self.names[wire].clone());
+                return cycle;
+            }
+            seen.insert(wire, path.len());
+            path.push(wire);
+
+            let g = &self.gates[gate];
+            gate = [g.left, g.right]
+                .iter()
+                .filter_map(|w| self.drivers[*w])
+                .find(|d| pending[*d] > 0)
+                .expect("A stuck gate has a stuck input");
+        }
+    }
+}
+
+/// A circuit with its gates in dependency order, ready to simulate in one pass.
+pub struct Evaluator<'a> {
+    circuit: &'a Circuit,
+    order: Vec<usize>,
+}
+
+impl Evaluator<'_> {
+    /// Fire every gate once, in order, updating `values`.
+    pub fn run(&self, values: &mut [bool]) {
+        for i in &self.order {
+            let gate = &self.circuit.gates[*i];
+            values[gate.out] = gate.op.apply(values[gate.left], values[gate.right]);
+        }
+    }
+
+    /// Simulate with the `x` and `y` buses set and return the `z` bus.
+    pub fn add(&self, x: u64, y: u64) -> u64 {
+        let mut values = self.circuit.initial_values();
+        self.circuit.set_bus(&mut values, 'x', x);
+        self.circuit.set_bus(&mut values, 'y', y);
+        self.run(&mut values);
+        self.circuit.read_bus(&values, 'z')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL: &str = "x00: 1\nx01: 0\ny00: 1\ny01: 1\n\n\
+        x00 XOR y00 -> z00\n\
+        x00 AND y00 -> c00\n\
+        x01 XOR y01 -> s01\n\
+        s01 XOR c00 -> z01\n\
+        x01 AND y01 -> a01\n\
+        s01 AND c00 -> b01\n\
+        a01 OR b01 -> z02\n";
+
+    #[test]
+    fn simulates_buses() {
+        let circuit = Circuit::parse(SMALL).unwrap();
+        assert_eq!(circuit.bus('x').len(), 2);
+        assert_eq!(circuit.name(circuit.bus('z')[2]), "z02");
+
+        let evaluator = circuit.evaluator().unwrap();
+        let mut values = circuit.initial_values();
+        evaluator.run(&mut values);
+        assert_eq!(circuit.read_bus(&values, 'z'), 1 + 3);
+
+        for (x, y) in (0..4).cartesian_product(0..4) {
+            assert_eq!(evaluator.add(x, y), x + y);
+        }
+    }
+
+    #[test]
+    fn reports_loops() {
+        let mut circuit = Circuit::parse(SMALL).unwrap();
+        // The XOR that reads c00 now drives it too
+        let (c00, z01) = (circuit.wire("c00").unwrap(), circuit.wire("z01").unwrap());
+        circuit.swap_outputs(c00, z01).unwrap();
+
+        let err = circuit.evaluator().err().unwrap().to_string();
+        assert_eq!(err, "Circuit has a loop: c00 -> c00");
+
+        assert!(Circuit::parse("x00: 1\n\nx00 AND q -> z00\n").is_err());
+    }
+}
@@ -0,0 +1,284 @@
+//! The claw machines of day 13. Each button moves the claw by a fixed step, so reaching the prize
+//! means solving two linear equations in the number of presses, which is done exactly in
+//! integers.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use tracing::debug;
+
+use crate::util::extended_gcd;
+
+/// Added to both prize coordinates in part B.
+const PART_B_OFFSET: i64 = 10_000_000_000_000;
+
+/// How the machines are played.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    /// Tokens per press of each button.
+    pub a_cost: i64,
+    pub b_cost: i64,
+    /// Most times either button may be pressed.
+    pub press_limit: Option<i64>,
+    /// Added to both prize coordinates.
+    pub offset: i64,
+}
+
+impl Rules {
+    pub const PART_A: Rules = Rules {
+        a_cost: 3,
+        b_cost: 1,
+        press_limit: Some(100),
+        offset: 0,
+    };
+
+    pub const PART_B: Rules = Rules {
+        press_limit: None,
+        offset: PART_B_OFFSET,
+        ..Rules::PART_A
+    };
+}
+
+/// The cheapest way to win a prize.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Win {
+    pub a: i64,
+    pub b: i64,
+    pub cost: i64,
+}
+
+pub struct ClawMachine {
+    pub a: (i64, i64),
+    pub b: (i64, i64),
+    pub prize: (i64, i64),
+}
+
+pub fn parse_machines(content: &str) -> Result<Vec<ClawMachine>> {
+    let button_a = Regex::new(r#"Button A: X\+([0-9]+), Y\+([0-9]+)"#).unwrap();
+    let button_b = Regex::new(r#"Button B: X\+([0-9]+), Y\+([0-9]+)"#).unwrap();
+    let prize = Regex::new(r#"Prize: X=([0-9]+), Y=([0-9]+)"#).unwrap();
+
+    let mut machines = Vec::new();
+    for block in content.split("\n\n") {
+        if block.trim().is_empty() {
+            continue;
+        }
+
+        let captures_a = button_a.captures(block).context("No Button A line")?;
+        let captures_b = button_b.captures(block).context("No Button B line")?;
+        let captures_p = prize.captures(block).context("No Prize line")?;
+
+        machines.push(ClawMachine {
+            a: (captures_a[1].parse()?, captures_a[2].parse()?),
+            b: (captures_b[1].parse()?, captures_b[2].parse()?),
+            prize: (captures_p[1].parse()?, captures_p[2].parse()?),
+        });
+    }
+
+    Ok(machines)
+}
+
+/// `x / y` rounded down, for positive `y`.
+fn div_floor(x: i128, y: i128) -> i128 {
+    x.div_euclid(y)
+}
+
+/// `x / y` rounded up, for positive `y`.
+fn div_ceil(x: i128, y: i128) -> i128 {
+    -(-x).div_euclid(y)
+}
+
+/// Cheapest `(a, b)` with `a * u + b * v = w`, neither negative nor over the press limit, when the
+/// buttons only move along one line. Steps are never negative, so there are only finitely many to
+/// choose from.
+fn solve_line(u: i128, v: i128, w: i128, rules: &Rules) -> Option<(i128, i128)> {
+    let limit = rules.press_limit.map(i128::from);
+    let within = |n: i128| limit.is_none_or(|l| n <= l);
+    match (u, v) {
+        _ if w < 0 => None,
+        (0, 0) => (w == 0).then_some((0, 0)),
+        (0, _) => (w % v == 0 && within(w / v)).then_some((0, w / v)),
+        (_, 0) => (w % u == 0 && within(w / u)).then_some((w / u, 0)),
+        _ => {
+            let (g, x, y) = extended_gcd(u as i64, v as i64);
+            let g = g as i128;
+            if w % g != 0 {
+                return None;
+            }
+            // Every solution is (a0 + t * v / g, b0 - t * u / g), and the cost changes by the
+            // same amount with each step of t, so the cheapest is at one end of the range that
+            // keeps both counts in bounds.
+            let (a0, b0) = (x as i128 * (w / g), y as i128 * (w / g));
+            let (da, db) = (v / g, u / g);
+            let (mut lowest, mut highest) = (div_ceil(-a0, da), div_floor(b0, db));
+            if let Some(l) = limit {
+                lowest = lowest.max(div_ceil(b0 - l, db));
+                highest = highest.min(div_floor(l - a0, da));
+            }
+            if lowest > highest {
+                return None;
+            }
+            let t = if rules.a_cost as i128 * da > rules.b_cost as i128 * db {
+                lowest
+            } else {
+                highest
+            };
+            Some((a0 + t * da, b0 - t * db))
+        }
+    }
+}
+
+impl ClawMachine {
+    /// Cheapest presses of A and B to land on the prize, if it can be reached within the rules.
+    pub fn solve(&self, rules: &Rules) -> Option<Win> {
+        let offset = rules.offset;
+        let (ax, ay) = (self.a.0 as i128, self.a.1 as i128);
+        let (bx, by) = (self.b.0 as i128, self.b.1 as i128);
+        let (px, py) = (
+            self.prize.0 as i128 + offset as i128,
+            self.prize.1 as i128 + offset as i128,
+        );
+
+        let det = ax * by - ay * bx;
+        let (a, b) = if det != 0 {
+            // Cramer's rule, which only counts when both divisions are exact
+            let a = px * by - py * bx;
+            let b = ax * py - ay * px;
+            if a % det != 0 || b % det != 0 {
+                return None;
+            }
+            (a / det, b / det)
+        } else {
+            // The buttons move along the same line, which the prize has to be on too
+            if ax * py != ay * px || bx * py != by * px {
+                return None;
+            }
+            if ax != 0 || bx != 0 {
+                solve_line(ax, bx, px, rules)?
+            } else {
+                solve_line(ay, by, py, rules)?
+            }
+        };
+
+        let limit = rules.press_limit.map(i128::from);
+        if a < 0 || b < 0 || limit.is_some_and(|l| a > l || b > l) {
+            return None;
+        }
+        let (a, b) = (a.try_into().ok()?, b.try_into().ok()?);
+        Some(Win {
+            a,
+            b,
+            cost: rules.a_cost * a + rules.b_cost * b,
+        })
+    }
+}
+
+/// How each machine can be won, if at all.
+pub fn play(machines: &[ClawMachine], rules: &Rules) -> Vec<Option<Win>> {
+    machines.iter().map(|m| m.solve(rules)).collect()
+}
+
+/// Tokens needed to win every prize that can be won.
+pub fn total_cost(machines: &[ClawMachine], rules: &Rules) -> i64 {
+    let mut total = 0;
+    for (i, win) in play(machines, rules).iter().enumerate() {
+        match win {
+            Some(Win { a, b, cost }) => {
+                debug!(machine = i, a, b, cost, "Won prize");
+                total += cost;
+            }
+            None => debug!(machine = i, "Can't win prize"),
+        }
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn machine(a: (i64, i64), b: (i64, i64), prize: (i64, i64)) -> ClawMachine {
+        ClawMachine { a, b, prize }
+    }
+
+    fn presses(machine: &ClawMachine, rules: &Rules) -> Option<(i64, i64)> {
+        machine.solve(rules).map(|w| (w.a, w.b))
+    }
+
+    #[test]
+    fn solves_independent_buttons() {
+        let first = machine((94, 34), (22, 67), (8400, 5400));
+        assert_eq!(
+            first.solve(&Rules::PART_A),
+            Some(Win {
+                a: 80,
+                b: 40,
+                cost: 280
+            })
+        );
+        assert_eq!(first.solve(&Rules::PART_B), None);
+
+        let second = machine((26, 66), (67, 21), (12748, 12176));
+        assert_eq!(second.solve(&Rules::PART_A), None);
+        assert_eq!(
+            presses(&second, &Rules::PART_B),
+            Some((118679050709, 103199174542))
+        );
+
+        // Only reachable with a negative number of presses
+        assert_eq!(
+            presses(&machine((1, 0), (0, 1), (-1, 5)), &Rules::PART_A),
+            None
+        );
+    }
+
+    #[test]
+    fn solves_collinear_buttons() {
+        let rules = Rules {
+            press_limit: None,
+            ..Rules::PART_A
+        };
+        let solve = |a, b, prize| presses(&machine(a, b, prize), &rules);
+        // A covers six units for 3 tokens, B one unit for 1, so use as many A as possible
+        assert_eq!(solve((6, 6), (1, 1), (13, 13)), Some((2, 1)));
+        // Now B is cheaper per unit
+        assert_eq!(solve((2, 2), (1, 1), (13, 13)), Some((0, 13)));
+        assert_eq!(solve((2, 4), (3, 6), (7, 14)), Some((2, 1)));
+        assert_eq!(solve((4, 4), (6, 6), (7, 7)), None);
+        assert_eq!(solve((1, 1), (2, 2), (3, 4)), None);
+        assert_eq!(solve((0, 2), (0, 3), (0, 7)), Some((2, 1)));
+        assert_eq!(solve((0, 0), (0, 0), (0, 0)), Some((0, 0)));
+    }
+
+    #[test]
+    fn follows_custom_rules() {
+        let first = machine((94, 34), (22, 67), (8400, 5400));
+        let strict = Rules {
+            press_limit: Some(50),
+            ..Rules::PART_A
+        };
+        assert_eq!(first.solve(&strict), None);
+        let pricey_b = Rules {
+            b_cost: 10,
+            ..Rules::PART_A
+        };
+        assert_eq!(first.solve(&pricey_b).map(|w| w.cost), Some(640));
+
+        // The limit pushes the collinear choice away from the cheapest
+        let line = machine((2, 2), (1, 1), (13, 13));
+        let limited = Rules {
+            press_limit: Some(10),
+            ..Rules::PART_A
+        };
+        assert_eq!(presses(&line, &limited), Some((2, 9)));
+        let shifted = Rules {
+            offset: 7,
+            ..limited
+        };
+        assert_eq!(presses(&line, &shifted), Some((5, 10)));
+
+        let machines = [first, line];
+        let wins = play(&machines, &Rules::PART_A);
+        assert_eq!(wins[1].map(|w| w.cost), Some(13));
+        assert_eq!(total_cost(&machines, &Rules::PART_A), 280 + 13);
+    }
+}
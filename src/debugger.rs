@@ -0,0 +1,115 @@
+//! Step through a day 17 program, printing every step and pausing at breakpoints.
+
+use std::io::{BufRead, Write};
+
+use anyhow::{Result, bail};
+
+use crate::machine::{Machine, STEP_LIMIT};
+
+pub struct Debugger {
+    pub machine: Machine,
+    /// Print the state before every step.
+    pub trace: bool,
+    /// Pause before executing the instruction at any of these addresses.
+    pub break_ips: Vec<usize>,
+    /// Pause once the output with any of these indices has been written.
+    pub break_outputs: Vec<usize>,
+}
+
+impl Debugger {
+    /// Run to the end, reading a command from `commands` whenever paused: `s` (or an empty line)
+    /// to step one instruction, `c` to continue to the next breakpoint and `q` to stop. Running
+    /// out of commands continues without pausing again.
+    pub fn run(&mut self, commands: &mut impl BufRead, out: &mut impl Write) -> Result<()> {
+        let mut stepping = false;
+        let mut interactive = true;
+        let mut output_hit = false;
+
+        loop {
+            let Some(instruction) = self.machine.current() else {
+                writeln!(
+                    out,
+                    "Halted after {} steps: {}",
+                    self.machine.steps,
+                    self.machine.output_string()
+                )?;
+                return Ok(());
+            };
+
+            let ip_hit = self.break_ips.contains(&self.machine.ip);
+            if interactive && (stepping || ip_hit || output_hit) {
+                writeln!(out, "Paused at {}  {}", self.machine, instruction)?;
+                write!(out, "(s)tep, (c)ontinue, (q)uit> ")?;
+                out.flush()?;
+
+                let mut line = String::new();
+                if commands.read_line(&mut line)? == 0 {
+                    writeln!(out)?;
+                    interactive = false;
+                } else {
+                    match line.trim() {
+                        "" | "s" => stepping = true,
+                        "c" => stepping = false,
+                        "q" => return Ok(()),
+                        other => {
+                            writeln!(out, "Unknown command {:?}", other)?;
+                            continue;
+                        }
+                    }
+                }
+            }
+
+            if self.trace {
+                writeln!(out, "{}  {}", self.machine, instruction)?;
+            }
+
+            if self.machine.steps >= STEP_LIMIT {
+                bail!("Program still running after {} steps", STEP_LIMIT);
+            }
+            let written = self.machine.output.len();
+            self.machine.step()?;
+            output_hit = self.machine.output.len() > written
+                && self
+                    .break_outputs
+                    .contains(&(self.machine.output.len() - 1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn debug(break_ips: Vec<usize>, break_outputs: Vec<usize>, commands: &str) -> String {
+        let mut debugger = Debugger {
+            machine: Machine::new(10, 0, 0, vec![5, 0, 5, 1, 5, 4]),
+            trace: false,
+            break_ips,
+            break_outputs,
+        };
+        let mut out = Vec::new();
+        debugger.run(&mut commands.as_bytes(), &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn pauses_at_breakpoints() {
+        let out = debug(vec![2], vec![], "s\nc\n");
+        let paused: Vec<&str> = out
+            .lines()
+            .filter_map(|l| l.find("Paused").map(|i| &l[i..]))
+            .collect();
+        assert_eq!(
+            paused,
+            [
+                "Paused at ip=2   a=10 b=0 c=0 out=[0]  out 1%8",
+                "Paused at ip=4   a=10 b=0 c=0 out=[0,1]  out A%8",
+            ]
+        );
+        assert!(out.ends_with("Halted after 3 steps: 0,1,2\n"), "{}", out);
+
+        let out = debug(vec![], vec![0], "q\n");
+        assert!(out.starts_with("Paused at ip=2 "), "{}", out);
+        assert!(!out.contains("Halted"), "{}", out);
+    }
+}
@@ -1,12 +1,20 @@
+use crate::adder;
+use crate::circuit::{Circuit, WireId};
+use crate::claw::{ClawMachine, Rules, parse_machines, total_cost};
+use crate::keypad::KeypadChain;
+use crate::machine::{BRUTE_FORCE_LIMIT, Machine, STEP_LIMIT, find_quine};
+use crate::render::{BACKGROUND, HIGHLIGHT, WALL};
+use crate::solution::{Answer, Solution};
+use crate::swarm::{PictureSearch, Swarm};
 use crate::util::*;
+use crate::warehouse::{RobotPlan, Warehouse};
 use itertools::Itertools;
-use rayon::prelude::*;
 
 use genetic_algorithm::{
-    crossover::{CrossoverMultiPoint, CrossoverSinglePoint},
+    crossover::CrossoverSinglePoint,
     fitness::{Fitness, FitnessChromosome, FitnessGenotype, FitnessValue},
     genotype::{Genotype, ListGenotype},
-    mutate::{MutateMultiGene, MutateSingleGene},
+    mutate::MutateMultiGene,
     select::SelectTournament,
     strategy::{
         Strategy,
@@ -16,113 +24,98 @@ use genetic_algorithm::{
 use std::{
     cmp::Ordering,
     collections::{HashMap, HashSet},
-    fs, io,
     iter::zip,
-    usize,
 };
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
+use num_bigint::BigUint;
 use regex::Regex;
-use tracing::{Level, debug, error, event, info, instrument, warn};
+use tracing::{Level, debug, enabled, instrument, warn};
 
-#[derive(Clone, Debug)]
-enum Gate {
-    And,
-    Or,
-    Xor,
-}
-
-#[derive(Clone, Debug)]
-struct GateConfig {
-    left: String,
-    right: String,
-    op: Gate,
+#[derive(Clone)]
+pub enum KeyLocks {
+    Key(Vec<i32>),
+    Lock(Vec<i32>),
 }
 
-#[derive(Clone, Debug)]
-enum GateNode {
-    Const(bool),
-    Gate(GateConfig),
-}
+pub struct Day25;
 
-fn eval(
-    name: String,
-    nodes: &HashMap<String, GateNode>,
-    visited: &mut HashSet<String>,
-) -> Option<bool> {
-    if visited.contains(&name) {
-        return None;
-    }
+impl Solution for Day25 {
+    type Input = Vec<KeyLocks>;
 
-    visited.insert(name.clone());
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        content
+            .split("\n\n")
+            .map(|block| {
+                let grid = Grid::parse_chars(block)?;
 
-    let node = nodes.get(&name).unwrap();
-    match node {
-        GateNode::Const(x) => {
-            return Some(*x);
-        }
-        GateNode::Gate(gate_config) => {
-            let mut a_clone = visited.clone();
-            a_clone.insert(gate_config.right.clone());
-            let a = eval(gate_config.left.clone(), nodes, &mut a_clone);
+                // Locks have their top row filled, keys their bottom row. Either way the height
+                // of a column is one less than the number of filled cells.
+                let is_key = !grid.row(0).contains(&'#');
+                let values: Vec<i32> = (0..grid.cols())
+                    .map(|c| grid.col(c).filter(|v| **v == '#').count() as i32 - 1)
+                    .collect();
 
-            let mut a_clone = visited.clone();
-            a_clone.insert(gate_config.left.clone());
-            let b = eval(gate_config.right.clone(), nodes, &mut a_clone);
+                Ok(if is_key {
+                    KeyLocks::Key(values)
+                } else {
+                    KeyLocks::Lock(values)
+                })
+            })
+            .collect()
+    }
 
-            if let None = a {
-                return None;
-            }
+    fn part_a(&self, kls: &Self::Input) -> Result<Answer> {
+        let keys: Vec<KeyLocks> = kls
+            .iter()
+            .filter(|x| matches!(x, KeyLocks::Key(_)))
+            .cloned()
+            .collect();
+        let locks: Vec<KeyLocks> = kls
+            .iter()
+            .filter(|x| matches!(x, KeyLocks::Lock(_)))
+            .cloned()
+            .collect();
 
-            if let None = b {
-                return None;
-            }
+        debug!(
+            key_len = keys.len(),
+            lock_len = locks.len(),
+            "Setup Complete"
+        );
 
-            match gate_config.op {
-                Gate::And => {
-                    return Some(a.unwrap() & b.unwrap());
-                }
-                Gate::Or => {
-                    return Some(a.unwrap() | b.unwrap());
-                }
-                Gate::Xor => {
-                    return Some(a.unwrap() ^ b.unwrap());
+        let mut total = 0;
+        for key in keys.iter().cloned() {
+            for lock in locks.iter().cloned() {
+                if let KeyLocks::Key(ref key_list) = key
+                    && let KeyLocks::Lock(ref lock_list) = lock
+                {
+                    let mut okay = true;
+                    for (key_v, lock_v) in zip(key_list, lock_list) {
+                        if key_v + lock_v > 5 {
+                            debug!(key_v, lock_v, "Failed");
+                            okay = false;
+                            break;
+                        }
+                    }
+                    if okay {
+                        total += 1;
+                    }
                 }
-            };
-        }
-    };
-}
-
-fn eval_z(z_gates: &Vec<String>, gates: &HashMap<String, GateNode>) -> Option<i64> {
-    let mut z_clone = z_gates.clone();
-    z_clone.sort_by_key(|x| {
-        let v: i32 = x[1..].parse().unwrap();
-        return -v;
-    });
-
-    let mut result: i64 = 0;
-    for gate in z_clone.iter().cloned() {
-        result = result << 1;
-        let mut visited: HashSet<String> = HashSet::new();
-        if let Some(x) = eval(gate, &gates, &mut visited) {
-            if x {
-                result += 1;
             }
-        } else {
-            return None;
         }
+
+        Ok(total.into())
     }
 
-    return Some(result);
+    fn part_b(&self, _kls: &Self::Input) -> Result<Answer> {
+        bail!("Day 25 only has one part")
+    }
 }
 
 #[derive(Debug, Clone)]
 struct SwapFitness<'a> {
-    gates: &'a HashMap<String, GateNode>,
-    all_gates: &'a Vec<(String, String)>,
-    z_gates: &'a Vec<String>,
-    x_gates: &'a Vec<String>,
-    y_gates: &'a Vec<String>,
+    circuit: &'a Circuit,
+    all_pairs: &'a [(WireId, WireId)],
 }
 impl Fitness for SwapFitness<'_> {
     type Genotype = ListGenotype;
@@ -133,160 +126,44 @@ impl Fitness for SwapFitness<'_> {
         _genotype: &FitnessGenotype<Self>,
     ) -> Option<FitnessValue> {
         // Check for overlapping swaps and bail if they exist
-        let indicies: Vec<usize> = chromosome.genes.clone();
-        let swaps: Vec<(String, String)> = indicies
+        let swaps: Vec<(WireId, WireId)> = chromosome
+            .genes
             .iter()
-            .map(|x| self.all_gates.get(*x).unwrap())
-            .cloned()
+            .map(|x| self.all_pairs[*x])
             .collect();
-
-        let mut gate_clone = self.gates.clone();
-        let mut unique: HashSet<String> = HashSet::new();
-        for i in 0..4 {
-            let a = swaps[i].0.clone();
-            let b = swaps[i].1.clone();
-
-            let a_v = self.gates.get(&a).unwrap();
-            let b_v = self.gates.get(&b).unwrap();
-
-            gate_clone.insert(a.clone(), b_v.clone());
-            gate_clone.insert(b.clone(), a_v.clone());
-
-            if unique.contains(&a) || unique.contains(&b) {
+        let mut unique: HashSet<WireId> = HashSet::new();
+        for (a, b) in swaps.iter() {
+            if !unique.insert(*a) || !unique.insert(*b) {
                 return None;
-            } else {
-                unique.insert(a);
-                unique.insert(b);
             }
         }
 
+        let swapped = self.circuit.with_swaps(&swaps).ok()?;
+        let evaluator = swapped.evaluator().ok()?;
+
         let mut fitness = 0;
-        for x_set in [22344970157743] {
-            let mut tx = x_set;
-            for v in self.x_gates.iter() {
-                gate_clone.insert(v.to_string(), GateNode::Const((tx & 1) == 1));
-                tx >>= 1;
-            }
+        for x_set in [22344970157743u64] {
             for y_set in 0..10 {
-                let mut ty = 1i64 << (2 * y_set);
-                for v in self.y_gates.iter() {
-                    gate_clone.insert(v.to_string(), GateNode::Const((ty & 1) == 1));
-                    ty >>= 1;
-                }
-
-                let result = eval_z(self.z_gates, &gate_clone);
-
-                if let Some(x) = result {
-                    let diff: i64 = (x_set + (1i64 << (2 * y_set)) as i64) ^ x;
-                    fitness += -1 * diff.count_ones() as i32;
-                } else {
-                    return None;
-                }
+                let y = 1u64 << (2 * y_set);
+                let diff = (x_set + y) ^ evaluator.add(x_set, y);
+                fitness += -(diff.count_ones() as i32);
             }
         }
 
-        return Some(fitness.try_into().unwrap());
+        Some(fitness.try_into().unwrap())
     }
 }
 
-#[instrument]
-pub fn day24(filename: String, part_b: bool) -> Result<()> {
-    let content = fs::read_to_string(filename).context("Couldn't read input")?;
-
-    let mut gates: HashMap<String, GateNode> = HashMap::new();
-    let mut z_gates: Vec<String> = Vec::new();
-    let mut x_gates: Vec<String> = Vec::new();
-    let mut y_gates: Vec<String> = Vec::new();
-
-    let mut constants = true;
-
-    for line in content.lines() {
-        if line.len() == 0 {
-            constants = false;
-            continue;
-        }
-
-        if constants {
-            let (l, r) = line.split_once(": ").unwrap();
-            gates.insert(l.to_string(), GateNode::Const(r == "1"));
-            if l.chars().nth(0).unwrap() == 'x' {
-                x_gates.push(l.to_string());
-            }
-            if l.chars().nth(0).unwrap() == 'y' {
-                y_gates.push(l.to_string());
-            }
-        } else {
-            let (l, name) = line.split_once(" -> ").unwrap();
-            let parts: Vec<&str> = l.split(" ").collect();
-            let a = parts[0];
-            let b = parts[2];
-            if name.chars().nth(0).unwrap() == 'z' {
-                z_gates.push(name.to_string());
-            }
-            match parts[1] {
-                "AND" => {
-                    gates.insert(
-                        name.to_string(),
-                        GateNode::Gate(GateConfig {
-                            left: a.to_string(),
-                            right: b.to_string(),
-                            op: Gate::And,
-                        }),
-                    );
-                }
-                "OR" => {
-                    gates.insert(
-                        name.to_string(),
-                        GateNode::Gate(GateConfig {
-                            left: a.to_string(),
-                            right: b.to_string(),
-                            op: Gate::Or,
-                        }),
-                    );
-                }
-                "XOR" => {
-                    gates.insert(
-                        name.to_string(),
-                        GateNode::Gate(GateConfig {
-                            left: a.to_string(),
-                            right: b.to_string(),
-                            op: Gate::Xor,
-                        }),
-                    );
-                }
-                _ => {
-                    error!("Unexpected Operation");
-                }
-            };
-        }
-    }
-
-    let result = eval_z(&z_gates, &gates);
-
-    info!(result, "Done");
-
-    // Part b
-    x_gates.sort_by_key(|x| {
-        let v: i32 = x[1..].parse().unwrap();
-        return v;
-    });
-
-    y_gates.sort_by_key(|x| {
-        let v: i32 = x[1..].parse().unwrap();
-        return v;
-    });
-
-    let all_gates: Vec<String> = gates
-        .keys()
-        .filter(|x| (x.chars().nth(0).unwrap() != 'x') && (x.chars().nth(0).unwrap() != 'y'))
-        .cloned()
+/// Search for four swaps that make the circuit add, scoring candidates on a handful of sums.
+/// Slow and not guaranteed to finish, only used when the adder structure can't be repaired.
+fn genetic_swaps(circuit: &Circuit) -> Result<Vec<(WireId, WireId)>> {
+    let outputs: Vec<WireId> = circuit.gates().iter().map(|g| g.out).collect();
+    let all_pairs: Vec<(WireId, WireId)> = outputs
+        .iter()
+        .tuple_combinations()
+        .map(|(a, b)| (*a, *b))
         .collect();
 
-    let mut all_pairs: Vec<(String, String)> = Vec::new();
-    for pair in all_gates.iter().combinations(2).unique() {
-        all_pairs.push((pair[0].to_string(), pair[1].to_string()));
-    }
-
     let genotype = ListGenotype::builder()
         .with_genes_size(4)
         .with_allele_list((0..all_pairs.len()).collect())
@@ -294,11 +171,8 @@ pub fn day24(filename: String, part_b: bool) -> Result<()> {
         .unwrap();
 
     let fitness = SwapFitness {
-        gates: &gates,
-        all_gates: &all_pairs,
-        z_gates: &z_gates,
-        x_gates: &x_gates,
-        y_gates: &y_gates,
+        circuit,
+        all_pairs: &all_pairs,
     };
 
     let mut evolve = Evolve::builder()
@@ -316,138 +190,176 @@ pub fn day24(filename: String, part_b: bool) -> Result<()> {
 
     evolve.call();
 
-    if let Some(best_genes) = evolve.best_genes() {
-        let selected_items = best_genes.clone();
-        let mut best: Vec<String> = Vec::new();
-        let mut gate_clone = gates.clone();
-        selected_items.iter().for_each(|x| {
-            let a: (String, String) = all_pairs.get(*x).unwrap().clone();
-            best.push(a.0.clone());
-            best.push(a.1.clone());
-
-            let b = a.1.clone();
-            let a = a.0.clone();
+    let best_genes = evolve.best_genes().context("All Garbage")?;
+    let swaps: Vec<(WireId, WireId)> = best_genes.iter().map(|x| all_pairs[*x]).collect();
 
-            let a_v = gates.get(&a).unwrap();
-            let b_v = gates.get(&b).unwrap();
+    let swapped = circuit.with_swaps(&swaps)?;
+    let mut values = swapped.initial_values();
+    swapped.evaluator()?.run(&mut values);
+    debug!(result = swapped.read_bus(&values, 'z'), "Swapped Result");
 
-            gate_clone.insert(a.clone(), b_v.clone());
-            gate_clone.insert(b.clone(), a_v.clone());
-        });
+    Ok(swaps)
+}
 
-        let result = eval_z(&z_gates, &gate_clone);
-        info!(final=best.iter().sorted().join(","), result, "done");
-    } else {
-        info!("All Garbage");
-    }
+/// Random additions tried on a repaired adder before trusting the swaps.
+const ADDER_SAMPLES: usize = 1000;
 
-    Ok(())
+pub struct Day24 {
+    pub genetic: bool,
 }
 
-#[instrument]
-pub fn day23(filename: String, part_b: bool) -> Result<()> {
-    let content = fs::read_to_string(filename).context("Couldn't read input")?;
+impl Solution for Day24 {
+    type Input = Circuit;
 
-    let mut links: HashMap<String, Vec<String>> = HashMap::new();
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        Circuit::parse(content)
+    }
 
-    content.lines().for_each(|line| {
-        let (l, r) = line.split_once('-').unwrap();
-        links
-            .entry(l.to_string())
-            .and_modify(|e| {
-                e.push(r.to_string());
-            })
-            .or_insert(vec![r.to_string()]);
-        links
-            .entry(r.to_string())
-            .and_modify(|e| {
-                e.push(l.to_string());
-            })
-            .or_insert(vec![l.to_string()]);
-    });
+    fn part_a(&self, circuit: &Self::Input) -> Result<Answer> {
+        let evaluator = circuit.evaluator()?;
+        let mut values = circuit.initial_values();
+        evaluator.run(&mut values);
+        Ok(circuit.read_bus(&values, 'z').into())
+    }
 
-    debug!(len = links.len(), "Links Read");
-
-    let mut max_len = 0;
-    let mut sets: HashSet<(String, String, String)> = HashSet::new();
-    for (key, value) in links.iter() {
-        // The most it could be is all the ones in value. For each of them, check how many contain
-        // each other.
-        let mut values = value.clone();
-        let mut done = false;
-        while !done {
-            done = true;
-            for (i, v) in values.clone().iter().enumerate() {
-                // Does it contain key?
-                let i_list = links.get(v).unwrap_or(&vec![]).clone();
-                if !i_list.contains(key) {
-                    values.remove(i);
-                    done = false;
-                    break;
-                }
-                // Does it contain all the other values?
-                for v_other in values.iter() {
-                    if v_other == v {
-                        continue;
-                    }
-                    if !i_list.contains(v_other) {
-                        values.remove(i);
-                        done = false;
-                        break;
-                    }
-                }
-                if !done {
-                    break;
-                }
+    fn part_b(&self, circuit: &Self::Input) -> Result<Answer> {
+        let swaps = match adder::repair(circuit) {
+            Ok(repairs) => repairs.into_iter().map(|r| r.swap).collect(),
+            Err(e) if self.genetic => {
+                warn!("Falling back to a genetic search: {:#}", e);
+                genetic_swaps(circuit)?
             }
+            Err(e) => return Err(e),
+        };
+
+        if let Some(mismatch) = adder::test_swaps(circuit, &swaps, ADDER_SAMPLES)? {
+            bail!("Swapped circuit still doesn't add: {}", mismatch);
         }
-        values.push(key.clone());
-        if values.len() > max_len {
-            values.sort();
-            info!(v = values.join(","), "New Max");
-        }
-        max_len = max_len.max(values.len());
 
-        if key.chars().nth(0).unwrap_or('x') != 't' {
-            continue;
+        Ok(swaps
+            .iter()
+            .flat_map(|(a, b)| [circuit.name(*a), circuit.name(*b)])
+            .sorted()
+            .join(",")
+            .into())
+    }
+}
+
+pub struct Day23;
+
+impl Solution for Day23 {
+    type Input = HashMap<String, Vec<String>>;
+
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        let mut links: HashMap<String, Vec<String>> = HashMap::new();
+
+        for line in content.lines() {
+            let (l, r) = line.split_once('-').context("Bad link line")?;
+            links
+                .entry(l.to_string())
+                .and_modify(|e| {
+                    e.push(r.to_string());
+                })
+                .or_insert(vec![r.to_string()]);
+            links
+                .entry(r.to_string())
+                .and_modify(|e| {
+                    e.push(l.to_string());
+                })
+                .or_insert(vec![l.to_string()]);
         }
 
-        debug!(key, ?value, "Checking link");
+        debug!(len = links.len(), "Links Read");
 
-        // Search the list of connections and list all recipricated connections
-        let reverse_connected: Vec<String> = value
-            .iter()
-            .filter(|&v| {
-                let entry: Vec<String> = links.get(v).unwrap_or(&vec![]).clone();
-                debug!(len = entry.len(), ?entry, "Got Other Links");
-                return entry.contains(key);
-            })
-            .cloned()
-            .collect();
+        Ok(links)
+    }
 
-        debug!(len = reverse_connected.len(), "Found Reverse Connections");
+    fn part_a(&self, links: &Self::Input) -> Result<Answer> {
+        let mut sets: HashSet<(String, String, String)> = HashSet::new();
+        for (key, value) in links.iter() {
+            if !key.starts_with('t') {
+                continue;
+            }
 
-        // From the filtered list, find any connected to one another
-        for (i, item_i) in reverse_connected.iter().cloned().enumerate() {
-            let i_list = links.get(&item_i).unwrap_or(&vec![]).clone();
-            for (j, item_j) in reverse_connected.iter().cloned().enumerate() {
-                if i == j {
-                    continue;
-                }
-                let j_list = links.get(&item_j).unwrap_or(&vec![]).clone();
-                if i_list.contains(&item_j) && j_list.contains(&item_i) {
-                    let mut triple = vec![item_i.clone(), item_j.clone(), key.to_string()];
-                    triple.sort();
+            debug!(key, ?value, "Checking link");
 
-                    sets.insert((triple[0].clone(), triple[1].clone(), triple[2].clone()));
+            // Search the list of connections and list all recipricated connections
+            let reverse_connected: Vec<String> = value
+                .iter()
+                .filter(|&v| {
+                    let entry: Vec<String> = links.get(v).unwrap_or(&vec![]).clone();
+                    debug!(len = entry.len(), ?entry, "Got Other Links");
+                    entry.contains(key)
+                })
+                .cloned()
+                .collect();
+
+            debug!(len = reverse_connected.len(), "Found Reverse Connections");
+
+            // From the filtered list, find any connected to one another
+            for (i, item_i) in reverse_connected.iter().cloned().enumerate() {
+                let i_list = links.get(&item_i).unwrap_or(&vec![]).clone();
+                for (j, item_j) in reverse_connected.iter().cloned().enumerate() {
+                    if i == j {
+                        continue;
+                    }
+                    let j_list = links.get(&item_j).unwrap_or(&vec![]).clone();
+                    if i_list.contains(&item_j) && j_list.contains(&item_i) {
+                        let mut triple = [item_i.clone(), item_j.clone(), key.to_string()];
+                        triple.sort();
+
+                        sets.insert((triple[0].clone(), triple[1].clone(), triple[2].clone()));
+                    }
                 }
             }
         }
+
+        Ok(sets.len().into())
     }
 
-    info!(len = sets.len(), max_len, "Done");
+    fn part_b(&self, links: &Self::Input) -> Result<Answer> {
+        let mut best: Vec<String> = Vec::new();
+        for (key, value) in links.iter() {
+            // The most it could be is all the ones in value. For each of them, check how many contain
+            // each other.
+            let mut values = value.clone();
+            let mut done = false;
+            while !done {
+                done = true;
+                for (i, v) in values.clone().iter().enumerate() {
+                    // Does it contain key?
+                    let i_list = links.get(v).unwrap_or(&vec![]).clone();
+                    if !i_list.contains(key) {
+                        values.remove(i);
+                        done = false;
+                        break;
+                    }
+                    // Does it contain all the other values?
+                    for v_other in values.iter() {
+                        if v_other == v {
+                            continue;
+                        }
+                        if !i_list.contains(v_other) {
+                            values.remove(i);
+                            done = false;
+                            break;
+                        }
+                    }
+                    if !done {
+                        break;
+                    }
+                }
+            }
+            values.push(key.clone());
+            if values.len() > best.len() {
+                values.sort();
+                debug!(v = values.join(","), "New Max");
+                best = values;
+            }
+        }
 
-    Ok(())
+        Ok(best.join(",").into())
+    }
 }
 
 fn process(number: u64) -> u64 {
@@ -460,387 +372,212 @@ fn process(number: u64) -> u64 {
 
     let f = e << 11;
     let g = f ^ e; // g is the secret now
-    let h = g % 16777216;
 
-    return h;
+    g % 16777216
 }
 
-#[instrument]
-pub fn day22(filename: String, part_b: bool, steps: i32) -> Result<()> {
-    let content = fs::read_to_string(filename).context("Couldn't read input")?;
-
-    let numbers: Vec<u64> = content.lines().map(|x| x.parse().unwrap()).collect();
-    let mut sequences: HashMap<(i8, i8, i8, i8), i32> = HashMap::new();
-
-    let mut sum: u64 = 0;
-    for number in numbers {
-        let mut value = number;
-        let mut changes: Vec<i8> = Vec::new();
-        let mut values: Vec<i8> = Vec::new();
-        let mut last: i8 = 0;
-        for i in 0..steps {
-            value = process(value);
-            if i > 0 {
-                changes.push((value % 10) as i8 - last);
-            }
-            last = (value % 10) as i8;
-            values.push(last);
-        }
-        sum += value;
-        debug!(value = value, "Step");
-
-        // Store all the 4 step combos and results (once with the earliest ones)
-        let mut seen: HashSet<(i8, i8, i8, i8)> = HashSet::new();
-        for offset in 3..changes.len() {
-            let key_parts = &changes[offset - 3..=offset];
-            let value = values[offset + 1];
-            let key: (i8, i8, i8, i8) = (key_parts[0], key_parts[1], key_parts[2], key_parts[3]);
-            if !seen.contains(&key) {
-                sequences
-                    .entry(key)
-                    .and_modify(|x| {
-                        *x += value as i32;
-                    })
-                    .or_insert(value as i32);
-                seen.insert(key);
-            }
-        }
-    }
-
-    let max_b = sequences.iter().map(|(k, v)| *v).max();
-
-    info!(sum, max_b, "Done");
-
-    Ok(())
+pub struct Day22 {
+    pub steps: i32,
 }
 
-fn directions_to_keys(directions: &Vec<Direction>) -> Vec<char> {
-    let mut result: Vec<char> = Vec::new();
-    for dir in directions {
-        result.push(match dir {
-            Direction::North => '^',
-            Direction::East => '>',
-            Direction::West => '<',
-            Direction::South => 'v',
-        });
+impl Solution for Day22 {
+    type Input = Vec<u64>;
+
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        content
+            .lines()
+            .map(|x| x.parse().context("Bad secret number"))
+            .collect()
     }
-    result.push('A');
-    return result;
-}
-
-fn generate_short_path(
-    keypad: &HashMap<char, (i32, i32)>,
-    empty: (i32, i32),
-    target: &String,
-    tricky: bool,
-) -> Result<Vec<String>> {
-    let mut current: (i32, i32) = keypad.get(&'A').context("No A Key Found!")?.clone();
-
-    let mut result: Vec<String> = Vec::new();
-    result.push("".to_string());
-
-    for c in target.chars() {
-        let mut directions_v: Vec<Direction> = Vec::new();
-        let mut directions_h: Vec<Direction> = Vec::new();
-        let target = keypad.get(&c).context("Unexpected Character Requested")?;
-        let dr: i32 = target.0 - current.0;
-        let dc: i32 = target.1 - current.1;
-
-        for _ in 0..dc.abs() {
-            if dc < 0 {
-                directions_h.push(Direction::West);
-            } else if dc > 0 {
-                directions_h.push(Direction::East);
-            }
-        }
 
-        for _ in 0..dr.abs() {
-            if dr < 0 {
-                directions_v.push(Direction::North);
-            } else if dr > 0 {
-                directions_v.push(Direction::South);
+    fn part_a(&self, numbers: &Self::Input) -> Result<Answer> {
+        let mut sum: u64 = 0;
+        for number in numbers {
+            let mut value = *number;
+            for _ in 0..self.steps {
+                value = process(value);
             }
+            sum += value;
+            debug!(value = value, "Step");
         }
 
-        let mut new_results: Vec<String> = Vec::new();
+        Ok(sum.into())
+    }
 
-        // Surely only LR then UD or UD then LR matter
-        let mut vh = directions_v.clone();
-        vh.extend(directions_h.clone());
+    fn part_b(&self, numbers: &Self::Input) -> Result<Answer> {
+        let mut sequences: HashMap<(i8, i8, i8, i8), i32> = HashMap::new();
 
-        let mut hv = directions_h.clone();
-        hv.extend(directions_v.clone());
-        /*
-        for permutation in directions
-            .iter()
-            .cloned()
-            .permutations(directions.len())
-            .unique()
-        */
-        let mut choices: Vec<Vec<Direction>> = Vec::new();
-        if tricky {
-            if dc == 0 || dr == 0 {
-                choices.push(hv);
-            } else if dr > 0 && dc > 0 {
-                choices.push(vh);
-            } else if dr > 0 && dc < 0 {
-                if dc == -1 {
-                    choices.push(hv);
-                    choices.push(vh);
-                } else {
-                    choices.push(vh);
-                }
-            } else if dr < 0 && dc < 0 {
-                if dc == -1 {
-                    choices.push(hv);
-                    choices.push(vh);
-                } else {
-                    choices.push(vh);
-                }
-            } else {
-                // dr < 0 && dc > 0
-                if dc == 1 {
-                    choices.push(vh);
-                    choices.push(hv);
-                } else {
-                    choices.push(hv);
+        for number in numbers {
+            let mut value = *number;
+            let mut changes: Vec<i8> = Vec::new();
+            let mut values: Vec<i8> = Vec::new();
+            let mut last: i8 = 0;
+            for i in 0..self.steps {
+                value = process(value);
+                if i > 0 {
+                    changes.push((value % 10) as i8 - last);
                 }
+                last = (value % 10) as i8;
+                values.push(last);
             }
-        } else {
-            choices.push(vh);
-            choices.push(hv);
-        }
 
-        for permutation in choices.iter() {
-            let mut test = current;
-            let mut keep = true;
-            for step in permutation.iter().cloned() {
-                let new_pos = move_direction(&test, &step);
-                if new_pos == empty {
-                    keep = false;
-                    // We can never actually get here apparently based on how we've set things up
-                }
-                test = new_pos;
-            }
-            if keep {
-                for current_string in result.iter().cloned() {
-                    let mut new_string = current_string.clone();
-                    new_string.extend(directions_to_keys(&permutation));
-                    new_results.push(new_string);
-                }
-                if tricky {
-                    break;
+            // Store all the 4 step combos and results (once with the earliest ones)
+            let mut seen: HashSet<(i8, i8, i8, i8)> = HashSet::new();
+            for offset in 3..changes.len() {
+                let key_parts = &changes[offset - 3..=offset];
+                let value = values[offset + 1];
+                let key: (i8, i8, i8, i8) =
+                    (key_parts[0], key_parts[1], key_parts[2], key_parts[3]);
+                if !seen.contains(&key) {
+                    sequences
+                        .entry(key)
+                        .and_modify(|x| {
+                            *x += value as i32;
+                        })
+                        .or_insert(value as i32);
+                    seen.insert(key);
                 }
             }
         }
-        result = new_results.iter().unique().cloned().collect();
-        current = target.clone();
-    }
 
-    Ok(result)
+        let max_b = sequences.values().copied().max().context("No sequences")?;
+
+        Ok(max_b.into())
+    }
 }
 
-#[instrument]
-pub fn day21(filename: String, part_b: bool) -> Result<()> {
-    let content = fs::read_to_string(filename).context("Couldn't read input")?;
-
-    let codes: Vec<String> = content.lines().map(|x| x.to_string()).collect();
-
-    let numeric_pad: HashMap<char, (i32, i32)> = HashMap::from([
-        ('0', (3, 1)),
-        ('A', (3, 2)),
-        ('1', (2, 0)),
-        ('2', (2, 1)),
-        ('3', (2, 2)),
-        ('4', (1, 0)),
-        ('5', (1, 1)),
-        ('6', (1, 2)),
-        ('7', (0, 0)),
-        ('8', (0, 1)),
-        ('9', (0, 2)),
-    ]);
-    let numeric_empty: (i32, i32) = (3, 0);
-
-    let key_pad: HashMap<char, (i32, i32)> = HashMap::from([
-        ('<', (1, 0)),
-        ('v', (1, 1)),
-        ('>', (1, 2)),
-        ('^', (0, 1)),
-        ('A', (0, 2)),
-    ]);
-
-    let keypad_empty: (i32, i32) = (0, 0);
+pub struct Day21 {
+    pub robots: usize,
+}
 
-    let mut total = 0;
+/// Total complexity of typing each code through `robots` robots on directional keypads.
+fn keypad_complexity(codes: &[String], robots: usize) -> Result<BigUint> {
+    let mut chain = KeypadChain::door(robots);
+    let mut total = BigUint::ZERO;
 
-    let steps: i32;
+    for code in codes {
+        let presses = chain.presses(code)?;
+        let digits: u64 = code
+            .trim_end_matches('A')
+            .parse()
+            .with_context(|| format!("Couldn't parse code digits of {}", code))?;
+        debug!(code, %presses, "Code Done");
 
-    if part_b {
-        steps = 25;
-    } else {
-        steps = 2;
+        total += presses * digits;
     }
 
-    for code in codes {
-        let step_1: Vec<String> = generate_short_path(&numeric_pad, numeric_empty, &code, false)
-            .context("Step 1 Failed")?;
+    Ok(total)
+}
 
-        let mut step_n: Vec<String> = step_1.clone();
-        let mut completed = 0;
+impl Solution for Day21 {
+    type Input = Vec<String>;
 
-        for _ in 0..steps {
-            debug!(len = step_n.len(), "Continuing");
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        Ok(content.lines().map(|x| x.to_string()).collect())
+    }
 
-            let next_step: Vec<String> = step_n
-                .par_iter()
-                .map(|input| generate_short_path(&key_pad, keypad_empty, &input, true).unwrap())
-                .flatten()
-                .collect();
+    fn part_a(&self, codes: &Self::Input) -> Result<Answer> {
+        Ok(keypad_complexity(codes, 2)?.into())
+    }
 
-            let min_len = next_step
-                .iter()
-                .map(|x| x.len())
-                .min()
-                .context("Nothing to len")?;
+    fn part_b(&self, codes: &Self::Input) -> Result<Answer> {
+        Ok(keypad_complexity(codes, self.robots)?.into())
+    }
+}
 
-            // Only keep the shortest sequences
-            step_n = next_step
-                .iter()
-                .filter(|x| x.len() == min_len)
-                .cloned()
-                .collect();
-            debug!(pre_l = next_step.len(), post_l = step_n.len(), "Filtered");
+/// A walled grid with a start `S` and end `E`, as used by days 16 and 20.
+pub struct Maze {
+    walls: Grid<bool>,
+    start: (i32, i32),
+    end: (i32, i32),
+}
 
-            completed += 1;
+impl Maze {
+    /// Inside the maze and not a wall.
+    fn is_open(&self, pos: Position) -> bool {
+        self.walls.get(pos) == Some(&false)
+    }
+}
 
-            if step_n.len() == 1 {
-                debug!(completed, "Leaving Part 1");
-                break;
-            }
-        }
+fn parse_maze(content: &str) -> Result<Maze> {
+    let chars = Grid::parse_chars(content)?;
 
-        if step_n.len() != 1 {
-            error!("More than 1 Option Left");
-        }
-        let next_step: String = step_n[0].clone();
-        let next_parts: Vec<String> = next_step
-            .split_inclusive('A')
-            .map(|x| x.to_string())
-            .collect();
-        let mut next_hist: HashMap<String, u64> = HashMap::new();
+    Ok(Maze {
+        start: chars.find(&'S').context("No start in the maze")?,
+        end: chars.find(&'E').context("No end in the maze")?,
+        walls: chars.map(|c| *c == '#'),
+    })
+}
 
-        for part in next_parts {
-            next_hist.entry(part).and_modify(|x| *x += 1).or_insert(1);
-        }
+pub struct Day20 {
+    pub threshold: i32,
+}
 
-        for _ in completed..steps {
-            let mut new_hist: HashMap<String, u64> = HashMap::new();
-            for (k, v) in next_hist.clone() {
-                let chunk = generate_short_path(&key_pad, keypad_empty, &k, true).unwrap();
-                let chunk_parts: Vec<String> = chunk[0]
-                    .split_inclusive('A')
-                    .map(|x| x.to_string())
-                    .collect();
-                for part in chunk_parts {
-                    new_hist.entry(part).and_modify(|x| *x += v).or_insert(v);
+impl Day20 {
+    /// Count the cheats of up to `max_d` steps that save at least `threshold` picoseconds.
+    fn count_cheats(&self, track: &Maze, max_d: i32) -> Result<usize> {
+        let Maze { walls, start, end } = track;
+        let (start, end) = (*start, *end);
+
+        let options = PathOptions {
+            path: true,
+            cells: true,
+        };
+        let paths = shortest_paths(start, end, walls, false, options);
+        let race = paths.path.context("No Path Found")?;
+        if paths.cells.is_some_and(|cells| cells.len() != race.len()) {
+            // Cheats are only counted from one of the routes
+            warn!("The race track has more than one best route");
+        }
+        let original_scores_reverse = shortest_distance(end, start, walls, false);
+        let original_time = race.len() as i32 - 1;
+
+        let mut cheats = 0;
+
+        // Walk the race track
+        // For each step, search a manhattan circle of max_d for any spots that save time
+        // Sum them
+        for (point_score, point) in race.iter().enumerate() {
+            let point_score = point_score as i32;
+            for dr in -max_d..=max_d {
+                for dc in -max_d..=max_d {
+                    if (dr.abs() + dc.abs() > max_d) || !track.is_open((point.0 + dr, point.1 + dc))
+                    {
+                        continue;
+                    }
+
+                    let steps = dr.abs() + dc.abs();
+                    let test_point = (point.0 + dr, point.1 + dc);
+                    let test_score = original_scores_reverse
+                        .get(&test_point)
+                        .context("No Test Point Score")?;
+                    let new_distance = point_score + steps + test_score;
+                    if original_time - new_distance >= self.threshold {
+                        debug!(?point, ?test_point, point_score, steps, "Got One");
+                        cheats += 1;
+                    }
                 }
             }
-            next_hist = new_hist;
         }
 
-        let mut len = 0;
-        for (k, v) in next_hist.clone() {
-            len += k.len() as u64 * v;
-        }
-        let digits: u64 = code[..3].parse().context("Couldn't parse code digits")?;
-        let complexity: u64 = digits * len as u64;
-        debug!(complexity, "Code Done");
-
-        total += complexity;
+        Ok(cheats)
     }
-
-    info!(total, "Done");
-
-    Ok(())
 }
 
-#[instrument]
-pub fn day20(filename: String, part_b: bool) -> Result<()> {
-    let content = fs::read_to_string(filename).context("Couldn't read input")?;
-
-    let mut walls: HashSet<(i32, i32)> = HashSet::new();
-    let mut not_walls: HashSet<(i32, i32)> = HashSet::new();
-    let mut start: (i32, i32) = (0, 0);
-    let mut end: (i32, i32) = (0, 0);
-
-    let mut rows: i32 = 0;
-    let mut cols: i32 = 0;
-
-    let mut wall_list: Vec<(i32, i32)> = Vec::new();
-
-    for (row, line) in content.lines().enumerate() {
-        rows += 1;
-        cols = line.len() as i32;
-        for (col, c) in line.chars().enumerate() {
-            match c {
-                '#' => {
-                    walls.insert((row as i32, col as i32));
-                    wall_list.push((row as i32, col as i32));
-                }
-                'S' => {
-                    start = (row as i32, col as i32);
-                }
-                'E' => {
-                    end = (row as i32, col as i32);
-                }
-                _ => {}
-            };
-        }
-    }
+impl Solution for Day20 {
+    type Input = Maze;
 
-    let original_scores = shortest_distance(start, end, &walls, rows, cols, false, None);
-    let original_scores_reverse = shortest_distance(end, start, &walls, rows, cols, false, None);
-    let original_time = original_scores.get(&end).context("No Path Found")?;
-
-    let mut cheat_routes: HashSet<((i32, i32), (i32, i32))> = HashSet::new();
-
-    let max_d;
-
-    if part_b {
-        max_d = 20;
-    } else {
-        max_d = 2;
-    }
-
-    // Trace the shortest route
-    // For each step, search a manhattan circle of max_d for any spots that save time
-    // Sum them
-    for (point, point_score) in original_scores.iter() {
-        for dr in -max_d..=max_d {
-            for dc in -max_d..=max_d {
-                if ((dr as i32).abs() + (dc as i32).abs() > max_d)
-                    || !is_in_bounds(rows, cols, point.0 + dr, point.1 + dc)
-                    || walls.contains(&(point.0 + dr, point.1 + dc))
-                {
-                    continue;
-                }
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        parse_maze(content)
+    }
 
-                let steps = dr.abs() + dc.abs();
-                let test_point = (point.0 + dr, point.1 + dc);
-                let test_score = original_scores_reverse
-                    .get(&test_point)
-                    .context("No Test Point Score")?;
-                let new_distance = point_score + steps + test_score;
-                if original_time - new_distance >= 100 {
-                    debug!(?point, ?test_point, point_score, steps, "Got One");
-                    cheat_routes.insert((*point, test_point));
-                }
-            }
-        }
+    fn part_a(&self, track: &Self::Input) -> Result<Answer> {
+        Ok(self.count_cheats(track, 2)?.into())
     }
 
-    info!(cheats = cheat_routes.len(), "Done");
-    Ok(())
+    fn part_b(&self, track: &Self::Input) -> Result<Answer> {
+        Ok(self.count_cheats(track, 20)?.into())
+    }
 }
 
 fn can_be_made(target: String, parts: &Vec<String>, cache: &mut HashMap<String, u64>) -> u64 {
@@ -864,7 +601,7 @@ fn can_be_made(target: String, parts: &Vec<String>, cache: &mut HashMap<String,
         if target.len() >= part.len() {
             let (l, r) = target.split_at(part.len());
             if l == part {
-                if r.len() == 0 {
+                if r.is_empty() {
                     debug!(l = l, r = r, cache_size = cache.len(), "Way Found");
                     ways += 1;
                 } else {
@@ -885,913 +622,373 @@ fn can_be_made(target: String, parts: &Vec<String>, cache: &mut HashMap<String,
     }
 
     cache.insert(target.clone(), ways);
-    return ways;
+    ways
 }
 
-#[instrument]
-pub fn day19(filename: String, part_b: bool) -> Result<()> {
-    let content = fs::read_to_string(filename).context("Couldn't read input")?;
-    let mut section_one = true;
+pub struct Towels {
+    parts: Vec<String>,
+    targets: Vec<String>,
+}
 
-    let mut parts: Vec<String> = Vec::new();
-    let mut targets: Vec<String> = Vec::new();
+/// Number of ways each target pattern can be made from the available towels.
+fn arrangements(towels: &Towels) -> Vec<u64> {
     let mut cache: HashMap<String, u64> = HashMap::new();
+    let mut result: Vec<u64> = Vec::new();
 
-    for line in content.lines() {
-        if line.len() == 0 {
-            section_one = false;
-            continue;
-        }
-
-        if section_one {
-            parts = line.split(", ").map(|x| x.to_string()).collect();
-        } else {
-            targets.push(line.to_string());
-        }
-    }
-
-    parts.sort_by_key(|x| -(x.len() as i32));
-
-    let mut total = 0;
-    let mut total_b = 0;
-    for target in targets {
-        info!(target = target, "Trying Next");
-        let result = can_be_made(target.clone(), &parts, &mut cache);
-        if result > 0 {
-            info!(target = ?target.clone(), cache_size=cache.len(), result=result, "Found Match");
-            total += 1;
-            total_b += result;
+    for target in towels.targets.iter() {
+        debug!(target = target, "Trying Next");
+        let ways = can_be_made(target.clone(), &towels.parts, &mut cache);
+        if ways > 0 {
+            debug!(target = ?target.clone(), cache_size=cache.len(), result=ways, "Found Match");
         } else {
-            info!(target = ?target.clone(), cache_size=cache.len(), result=result, "No Match");
+            debug!(target = ?target.clone(), cache_size=cache.len(), result=ways, "No Match");
         }
+        result.push(ways);
     }
 
-    info!(total = total, total_b = total_b, "Done");
-
-    Ok(())
-}
-fn display_18_map(map: &HashSet<(i32, i32)>, size: i32) {
-    for r in 0..=size {
-        for c in 0..=size {
-            if map.contains(&(r, c)) {
-                print!("#");
-            } else {
-                print!(".");
-            }
-        }
-        print!("\n");
-    }
+    result
 }
 
-#[instrument]
-pub fn day18(filename: String, part_b: bool, size: i32, steps: usize, first_jump: i32) {
-    let content = fs::read_to_string(filename).expect("Couldn't read input");
-
-    let mut position: (i32, i32) = (0, 0);
-    let mut goal: (i32, i32) = (size, size);
-
-    let mut blockers: Vec<(i32, i32)> = Vec::new();
+pub struct Day19;
 
-    for (r, line) in content.lines().enumerate() {
-        let (col, row) = line.split_once(',').unwrap();
-        blockers.push((row.parse::<i32>().unwrap(), col.parse::<i32>().unwrap()));
-    }
+impl Solution for Day19 {
+    type Input = Towels;
 
-    let mut steps = steps;
-    let mut jump = first_jump;
-    loop {
-        // Path find on a changing map. Could work with an exhaustive search.
-        let mut front: Vec<((i32, i32), i32)> = Vec::new();
-        let mut scores: HashMap<(i32, i32), i32> = HashMap::new();
-        front.push((position, 0)); // Store position + time
-        scores.insert(position, 0);
-
-        // Simulate X steps
-        let mut map: HashSet<(i32, i32)> = HashSet::new();
-        for t in 0..steps {
-            map.insert(blockers[t]);
-        }
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        let mut section_one = true;
 
-        // display_18_map(&map, size);
+        let mut parts: Vec<String> = Vec::new();
+        let mut targets: Vec<String> = Vec::new();
 
-        while let Some((pos, distance)) = front.pop() {
-            let mut early = false;
-            for dir in [
-                Direction::North,
-                Direction::South,
-                Direction::East,
-                Direction::West,
-            ] {
-                let test = move_direction(&pos, &dir);
-                if is_in_bounds(size + 1, size + 1, test.0, test.1) && !map.contains(&test) {
-                    let current = scores.get(&test).unwrap_or(&(distance + 2)).clone();
-                    if distance + 1 < current {
-                        front.push((test, distance + 1));
-                        scores.insert(test, distance + 1);
-                        if test == goal {
-                            early = true;
-                            break;
-                        }
-                    }
-                }
-            }
-            if early {
-                break;
+        for line in content.lines() {
+            if line.is_empty() {
+                section_one = false;
+                continue;
             }
-            front.sort_by_key(|x| x.1);
-        }
 
-        if let None = scores.get(&goal) {
-            if jump > 1 {
-                debug!(steps = jump, "little jump");
-                steps -= 2 * jump as usize;
-                jump = 1;
+            if section_one {
+                parts = line.split(", ").map(|x| x.to_string()).collect();
             } else {
-                info!(distance = steps, blocker = ?blockers[steps-1], "Finished");
-                break;
+                targets.push(line.to_string());
             }
-        } else {
-            debug!(steps = steps, "Continuing");
-            steps += jump as usize;
-        }
-    }
-}
-
-#[instrument]
-pub fn day17(filename: String, part_b: bool) {
-    let content = fs::read_to_string(filename).expect("Couldn't read input");
-
-    let mut register_a: u64 = 0;
-    let mut register_b: u64 = 0;
-    let mut register_c: u64 = 0;
-    let mut instruction_pointer: usize = 0;
-    let mut instructions: Vec<u8> = Vec::new();
-
-    let mut target: String = "".to_string();
-
-    for (i, line) in content.lines().enumerate() {
-        if line.len() == 0 {
-            continue;
         }
 
-        let (_, right) = line.split_once(": ").unwrap();
+        parts.sort_by_key(|x| -(x.len() as i32));
 
-        match i {
-            0 => {
-                register_a = right.parse().unwrap();
-            }
-            1 => {
-                register_b = right.parse().unwrap();
-            }
-            2 => {
-                register_c = right.parse().unwrap();
-            }
-            3 => {}
-            4 => {
-                instructions = right.split(',').map(|x| x.parse().unwrap()).collect();
-            }
-            _ => {}
-        }
+        Ok(Towels { parts, targets })
     }
-    debug!(a=register_a, b=register_b, c=register_c, instructions=?instructions, "Read Program");
 
-    let mut out_pointer;
-    let mut a_test = 0;
+    fn part_a(&self, towels: &Self::Input) -> Result<Answer> {
+        let total = arrangements(towels).iter().filter(|x| **x > 0).count();
+        Ok(total.into())
+    }
 
-    if part_b {
-        a_test = 0; //8u64.pow(instructions.len() as u32);
+    fn part_b(&self, towels: &Self::Input) -> Result<Answer> {
+        let total: u64 = arrangements(towels).iter().sum();
+        Ok(total.into())
+    }
+}
+fn display_18_map(map: &Grid<bool>, path: &[Position]) {
+    let mut chars = map.map(|b| if *b { '#' } else { '.' });
+    for pos in path {
+        chars[*pos] = 'O';
     }
+    debug!("\n{}", chars);
+}
 
-    loop {
-        register_a = (a_test << 24) + 0b11101001011011010010011100000;
-        register_b = 0;
-        register_c = 0;
-        instruction_pointer = 0;
+pub struct Day18 {
+    pub size: i32,
+    pub steps: usize,
+    pub first_jump: i32,
+}
 
-        out_pointer = 0;
-        loop {
-            if instruction_pointer >= instructions.len() {
-                debug!("End of Sequence");
-                break;
+impl Day18 {
+    /// The memory space with the given bytes fallen.
+    fn memory(&self, fallen: &[(i32, i32)]) -> Grid<bool> {
+        let mut map = Grid::new(self.size + 1, self.size + 1, false);
+        for pos in fallen {
+            if let Some(cell) = map.get_mut(*pos) {
+                *cell = true;
             }
-            let op_code = instructions[instruction_pointer];
-            let operand = instructions[instruction_pointer + 1];
-
-            let operand_value = match operand {
-                0 | 1 | 2 | 3 => operand as u64,
-                4 => register_a,
-                5 => register_b,
-                6 => register_c,
-                7 => 0,
-                _ => 0,
-            };
-
-            match op_code {
-                0 => {
-                    // adv
-                    let div = 2u64.pow(operand_value as u32) as u64;
-                    let result = register_a / div;
-                    register_a = result;
-                }
-                1 => {
-                    // bxl
-                    let result = register_b ^ operand as u64;
-                    register_b = result;
-                }
-                2 => {
-                    // bst
-                    let result = operand_value % 8;
-                    register_b = result;
-                }
-                3 => {
-                    // jnz
-                    if register_a != 0 {
-                        instruction_pointer = operand as usize;
-                        continue;
-                    }
-                }
-                4 => {
-                    // bxc
-                    register_b = register_b ^ register_c;
-                }
-                5 => {
-                    // out
-                    let result = operand_value % 8;
-                    if result as u8 != instructions[out_pointer as usize] {
-                        if out_pointer > 8 {
-                            debug!(
-                                a = a_test,
-                                out = out_pointer,
-                                "Bailed on output mismatch {:b}",
-                                a_test
-                            );
-                        }
-                        break;
-                    }
-
-                    out_pointer += 1;
-
-                    if out_pointer >= instructions.len() {
-                        debug!("Bailed on output Length");
-                    }
-                }
-                6 => {
-                    // bdv
-                    let div = 2u64.pow(operand_value as u32) as u64;
-                    let result = register_a / div;
-                    register_b = result;
-                }
-                7 => {
-                    // cdv
-                    let div = 2u64.pow(operand_value as u32) as u64;
-                    let result = register_a / div;
-                    register_c = result;
-                }
-                _ => {
-                    error!(op_code = op_code, "Invalid Op Code");
-                }
-            };
-            instruction_pointer += 2;
         }
-        if out_pointer == instructions.len() {
-            register_a = (a_test << 24) + 0b11101001011011010010011100000;
-            info!(a_test = register_a, "Finished Run");
-            break;
-        }
-
-        a_test += 1;
+        map
     }
-}
 
-fn display_map(walls: &HashSet<(i32, i32)>, spots: &HashSet<(i32, i32)>, rows: i32, cols: i32) {
-    for row in 0..rows {
-        for col in 0..cols {
-            if walls.contains(&(row, col)) {
-                print!("#");
-            } else if spots.contains(&(row, col)) {
-                print!("O");
-            } else {
-                print!(".");
-            }
-        }
-        print!("\n");
+    /// One shortest path from the top left corner to the exit, inclusive of both.
+    fn exit_path(&self, map: &Grid<bool>) -> Option<Vec<Position>> {
+        let goal: (i32, i32) = (self.size, self.size);
+        let moves = |pos: &Position| {
+            map.neighbors(*pos)
+                .filter(|(_, next)| !map[*next])
+                .map(|(_, next)| (next, 1))
+                .collect::<Vec<_>>()
+        };
+        dijkstra([(0, 0)], moves, |pos| *pos == goal).path_to(&goal)
     }
 }
 
-#[instrument]
-pub fn day16(filename: String, part_b: bool) {
-    let content = fs::read_to_string(filename).expect("Couldn't read input");
-
-    let mut walls: HashSet<(i32, i32)> = HashSet::new();
-    let mut start: (i32, i32) = (0, 0);
-    let mut end: (i32, i32) = (0, 0);
-
-    let mut rows: i32 = 0;
-    let mut cols: i32 = 0;
-
-    for (row, line) in content.lines().enumerate() {
-        rows += 1;
-        cols = line.len() as i32;
-        for (col, c) in line.chars().enumerate() {
-            match c {
-                '#' => {
-                    walls.insert((row as i32, col as i32));
-                }
-                'S' => {
-                    start = (row as i32, col as i32);
-                }
-                'E' => {
-                    end = (row as i32, col as i32);
-                }
-                _ => {}
-            };
-        }
+/// The fallen bytes of part A and a shortest path through them, for drawing.
+pub fn memory_picture(day: &Day18, blockers: &[Position]) -> Grid<usize> {
+    let map = day.memory(&blockers[..day.steps.min(blockers.len())]);
+    let mut cells = map.map(|byte| if *byte { WALL } else { BACKGROUND });
+    for pos in day.exit_path(&map).unwrap_or_default() {
+        cells[pos] = HIGHLIGHT;
     }
+    cells
+}
 
-    let mut scores: HashMap<(i32, i32), i32> = HashMap::new();
-    let mut spots: HashSet<(i32, i32)> = HashSet::new();
-    let mut front: Vec<((i32, i32), Direction, i32, HashSet<(i32, i32)>)> = Vec::new();
-
-    scores.insert(start, 0);
-    let mut start_spots: HashSet<(i32, i32)> = HashSet::new();
-    start_spots.insert(start);
-    front.push((start, Direction::East, 0, start_spots));
-
-    // TODO: There are multiple ways to step and turn with different scores at different points
-    // Storing only the absolute minimum is not going to cut it. Maybe need to be more exhaustive.
-
-    while let Some(next) = front.pop() {
-        // Straight
-        let next_spot = move_direction(&next.0, &next.1);
-        if !walls.contains(&next_spot) {
-            let current_score: i32 = *scores.get(&next_spot).unwrap_or(&(next.2 + 2));
-            if next.2 + 1 <= current_score {
-                scores.insert(next_spot, next.2 + 1);
-
-                let mut new_spots = next.3.clone();
-                new_spots.insert(next_spot);
+impl Solution for Day18 {
+    type Input = Vec<(i32, i32)>;
 
-                if next_spot == end {
-                    if next.2 + 1 == current_score {
-                        spots.extend(&new_spots);
-                    } else {
-                        spots = new_spots.clone();
-                    }
-                }
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        let mut blockers: Vec<(i32, i32)> = Vec::new();
 
-                front.insert(0, (next_spot, next.1.clone(), next.2 + 1, new_spots));
-            }
+        for line in content.lines() {
+            let (col, row) = line.split_once(',').context("Bad coordinate")?;
+            blockers.push((row.parse::<i32>()?, col.parse::<i32>()?));
         }
 
-        // Left & Right
-        for turning in [Rotation::Left, Rotation::Right] {
-            let test_direction = turn(&next.1, turning);
-            let next_spot = move_direction(&next.0, &test_direction);
-            if !walls.contains(&next_spot) {
-                let current_score: i32 = *scores.get(&next_spot).unwrap_or(&(next.2 + 1002));
-                if next.2 + 1001 <= current_score {
-                    scores.insert(next_spot, next.2 + 1001);
-
-                    let mut new_spots = next.3.clone();
-                    new_spots.insert(next_spot);
-
-                    if next_spot == end {
-                        if next.2 + 1001 == current_score {
-                            spots.extend(&new_spots);
-                        } else {
-                            spots = new_spots.clone();
-                        }
-                    }
+        Ok(blockers)
+    }
 
-                    // front.insert(0, (next_spot, test_direction, next.2 + 1001, new_spots));
-                    front.push((next_spot, test_direction, next.2 + 1001, new_spots));
-                }
-            }
+    fn part_a(&self, blockers: &Self::Input) -> Result<Answer> {
+        let map = self.memory(&blockers[..self.steps.min(blockers.len())]);
+        let path = self.exit_path(&map).context("No path to the exit")?;
+        if enabled!(Level::DEBUG) {
+            display_18_map(&map, &path);
         }
 
-        //front.sort_by_key(|x| x.2);
+        Ok((path.len() - 1).into())
     }
 
-    display_map(&walls, &spots, rows, cols);
+    fn part_b(&self, blockers: &Self::Input) -> Result<Answer> {
+        let size = self.size;
+        let position: (i32, i32) = (0, 0);
+        let goal: (i32, i32) = (size, size);
 
-    info!(score=?scores.get(&end), spots=?spots.len(), "Finished Pathfinding");
-}
+        let mut steps = self.steps;
+        let mut jump = self.first_jump;
+        loop {
+            // Simulate X steps
+            let map = self.memory(&blockers[..steps.min(blockers.len())]);
 
-#[derive(Debug, Clone, Copy)]
-enum Tile {
-    Wall,
-    Box,
-    Empty,
-    BoxLeft,
-    BoxRight,
-}
+            // display_18_map(&map, &[]);
 
-fn display_map_15(map: &HashMap<(i32, i32), Tile>, rows: i32, cols: i32, player: (i32, i32)) {
-    for row in 0..rows {
-        for col in 0..cols {
-            if player.0 == row && player.1 == col {
-                print!("@");
-            } else {
-                if let Some(x) = map.get(&(row, col)) {
-                    match x {
-                        Tile::Wall => print!("#"),
-                        Tile::Box => print!("O"),
-                        Tile::Empty => print!("."),
-                        Tile::BoxLeft => print!("["),
-                        Tile::BoxRight => print!("]"),
-                    };
+            let scores = shortest_distance(position, goal, &map, true);
+
+            if !scores.contains_key(&goal) {
+                if jump > 1 {
+                    debug!(steps = jump, "little jump");
+                    steps = steps.saturating_sub(2 * jump as usize);
+                    jump = 1;
                 } else {
-                    print!("?");
+                    let blocker = blockers[steps - 1];
+                    debug!(distance = steps, ?blocker, "Finished");
+                    return Ok(format!("{},{}", blocker.1, blocker.0).into());
                 }
+            } else if steps >= blockers.len() {
+                bail!("The exit is never blocked");
+            } else {
+                debug!(steps = steps, "Continuing");
+                steps += jump as usize;
             }
         }
-        print!("\n");
     }
 }
 
-#[instrument]
-pub fn day15(filename: String, part_b: bool) {
-    let content = fs::read_to_string(filename).expect("Couldn't read input");
-
-    let mut map_parse = true;
+pub struct Day17;
 
-    let mut map: HashMap<(i32, i32), Tile> = HashMap::new();
-    let mut player: (i32, i32) = (-1, -1);
+impl Solution for Day17 {
+    type Input = Machine;
 
-    let mut directions: Vec<Direction> = Vec::new();
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        let machine = Machine::parse(content)?;
+        debug!(a = machine.a, b = machine.b, c = machine.c, program = ?machine.program, "Read Program");
+        Ok(machine)
+    }
 
-    let mut rows = 0;
-    let mut cols = 0;
+    fn part_a(&self, machine: &Self::Input) -> Result<Answer> {
+        let mut machine = machine.clone();
+        machine.run(STEP_LIMIT)?;
+        Ok(machine.output_string().into())
+    }
 
-    for (row, line) in content.lines().enumerate() {
-        if line.len() == 0 {
-            map_parse = false;
-        }
+    fn part_b(&self, machine: &Self::Input) -> Result<Answer> {
+        Ok(find_quine(machine, BRUTE_FORCE_LIMIT)?.into())
+    }
+}
 
-        if map_parse {
-            rows += 1;
-            cols = line.len() as i32;
-            for (col, c) in line.chars().enumerate() {
-                match c {
-                    '#' => {
-                        if part_b {
-                            map.insert((row as i32, 2 * col as i32), Tile::Wall);
-                            map.insert((row as i32, 2 * col as i32 + 1), Tile::Wall);
-                        } else {
-                            map.insert((row as i32, col as i32), Tile::Wall);
-                        }
-                    }
-                    'O' => {
-                        if part_b {
-                            map.insert((row as i32, 2 * col as i32), Tile::BoxLeft);
-                            map.insert((row as i32, 2 * col as i32 + 1), Tile::BoxRight);
-                        } else {
-                            map.insert((row as i32, col as i32), Tile::Box);
-                        }
-                    }
-                    '.' => {
-                        if part_b {
-                            map.insert((row as i32, 2 * col as i32), Tile::Empty);
-                            map.insert((row as i32, 2 * col as i32 + 1), Tile::Empty);
-                        } else {
-                            map.insert((row as i32, col as i32), Tile::Empty);
-                        }
-                    }
-                    '@' => {
-                        if part_b {
-                            map.insert((row as i32, 2 * col as i32), Tile::Empty);
-                            map.insert((row as i32, 2 * col as i32 + 1), Tile::Empty);
-                            player = (row as i32, 2 * col as i32);
-                        } else {
-                            map.insert((row as i32, col as i32), Tile::Empty);
-                            player = (row as i32, col as i32);
-                        }
-                    }
-                    _ => {}
-                };
-            }
-        } else {
-            for c in line.chars() {
-                match c {
-                    '^' => {
-                        directions.push(Direction::North);
-                    }
-                    'v' => {
-                        directions.push(Direction::South);
-                    }
-                    '<' => {
-                        directions.push(Direction::West);
-                    }
-                    '>' => {
-                        directions.push(Direction::East);
-                    }
-                    _ => {
-                        warn!(char = ?c, "Found Bad Character");
-                    }
-                };
-            }
-        }
+fn display_map(walls: &Grid<bool>, spots: &HashSet<(i32, i32)>) {
+    let mut chars = walls.map(|wall| if *wall { '#' } else { '.' });
+    for spot in spots {
+        chars[*spot] = 'O';
     }
+    debug!("\n{}", chars);
+}
 
-    // Execute the instructions
-    for dir in directions {
-        let test_position = move_direction(&player, &dir);
-        debug!(player=?player, test_position=?test_position, dir=?dir, "Attempting to Move");
-        //display_map(&map, rows, cols * 2, player);
+/// Lowest score from start to end, along with every tile on a best path.
+fn best_paths(maze: &Maze) -> (Option<i32>, HashSet<Position>) {
+    // The reindeer can step forwards or turn on the spot
+    let moves = |(pos, d): &(Position, Direction)| {
+        let mut next = vec![
+            ((*pos, turn(d, Rotation::Left)), 1000),
+            ((*pos, turn(d, Rotation::Right)), 1000),
+        ];
+        let ahead = move_direction(pos, d);
+        if maze.is_open(ahead) {
+            next.push(((ahead, d.clone()), 1));
+        }
+        next
+    };
 
-        // If the direction is empty, just move.
-        match map.get(&test_position) {
-            None => {
-                continue;
-            }
-            Some(x) => match *x {
-                Tile::Wall => {
-                    debug!("Hit a Wall");
-                    continue;
-                }
-                Tile::Empty => {
-                    debug!("Moved to Empty");
-                    player = test_position;
-                    continue;
-                }
-                Tile::BoxLeft | Tile::BoxRight | Tile::Box => {
-                    debug!("Found a Box");
-                    // Keep moving in the same direction until you reach either an empty space or a
-                    // wall. Noting how many boxes you pass on the way.
-                    let mut search_positions: Vec<(i32, i32)> = Vec::new();
-
-                    let mut can_move = true;
-
-                    // for part b vertical movement, need to store a collection of left and right
-                    // pieces. For each, check they are clear above, when they are, put them into
-                    // another list of 'moveable' pieces. If the potentially moveable list is
-                    // exhasted and all end up in moveable, move them.
-                    let search_position = move_direction(&test_position, &dir);
-                    search_positions.push(search_position);
-
-                    if part_b {
-                        // If part b and moving up or down, add the other half of the box
-                        if matches!(dir, Direction::North | Direction::South) {
-                            if matches!(x, Tile::BoxLeft) {
-                                search_positions.push((search_position.0, search_position.1 + 1));
-                            } else if matches!(x, Tile::BoxRight) {
-                                search_positions.push((search_position.0, search_position.1 - 1));
-                            } else {
-                                error!(
-                                    search_position = ?search_position,
-                                    "Got Something other than a box half!"
-                                );
-                            }
-                        }
-                    }
+    let search = dijkstra([(maze.start, Direction::East)], moves, |(pos, _)| {
+        *pos == maze.end
+    });
 
-                    let mut to_fill: Vec<(i32, i32)> = Vec::new();
-                    while search_positions.len() > 0 {
-                        let mut new_positions: Vec<(i32, i32)> = Vec::new();
-                        for search_position in search_positions.iter().cloned() {
-                            let search_value = map.get(&search_position);
-                            if let Some(x) = search_value {
-                                match x {
-                                    Tile::Wall => {
-                                        debug!("Found a Wall, Can't Move");
-                                        can_move = false;
-                                        break;
-                                    }
-                                    Tile::Box => {}
-                                    Tile::BoxLeft => {
-                                        if matches!(dir, Direction::North | Direction::South) {
-                                            // Add the area above the box to the list to check
-                                            // Don't keep checking this one
-                                            let new_search_position =
-                                                move_direction(&search_position, &dir);
-                                            new_positions.push(new_search_position);
-                                            new_positions.push((
-                                                new_search_position.0,
-                                                new_search_position.1 + 1,
-                                            ));
-                                            to_fill.push(search_position);
-                                            continue;
-                                        }
-                                    }
-                                    Tile::BoxRight => {
-                                        if matches!(dir, Direction::North | Direction::South) {
-                                            // Add the area above the box to the list to check
-                                            // Don't keep checking this one
-                                            /*let new_search_position = move_direction(
-                                                &move_direction(&search_position, &dir),
-                                                &dir,
-                                            );*/
-                                            let new_search_position =
-                                                move_direction(&search_position, &dir);
-                                            new_positions.push(new_search_position);
-                                            new_positions.push((
-                                                new_search_position.0,
-                                                new_search_position.1 - 1,
-                                            ));
-                                            to_fill.push(search_position);
-                                            continue;
-                                        }
-                                    }
-                                    Tile::Empty => {
-                                        // Add this spot to the list to fill, don't keep checking
-                                        to_fill.push(search_position);
-                                        if matches!(dir, Direction::North | Direction::South) {
-                                            continue;
-                                        } else {
-                                            break; // TODO: Unsure
-                                        }
-                                    }
-                                };
-                                let new_search_position = move_direction(&search_position, &dir);
-                                new_positions.push(new_search_position);
-                            }
-                        }
-                        search_positions = new_positions;
-                    }
+    let Some(goal) = &search.goal else {
+        return (None, HashSet::new());
+    };
+    let score = search.distance(goal);
+
+    // The end can be reached facing different ways for the same score
+    let ends = Direction::CARDINAL
+        .map(|d| (maze.end, d))
+        .into_iter()
+        .filter(|end| search.distance(end) == score);
+
+    let spots: HashSet<Position> = search
+        .on_any_path(ends)
+        .into_iter()
+        .map(|(pos, _)| pos)
+        .collect();
 
-                    if can_move {
-                        // Search position will be an empty space, put a box there, move the player
-                        // once.
-                        if part_b {
-                            // Shuffle all the boxes
-                            if matches!(dir, Direction::East | Direction::West) {
-                                if to_fill.len() != 1 {
-                                    error!(len=?to_fill.len(),"More than one to_fill found");
-                                }
-                                let search_position = to_fill[0];
-                                let first_spot = move_direction(&test_position, &dir);
-                                let lower = search_position.1.min(first_spot.1);
-                                let upper = search_position.1.max(first_spot.1);
-                                let mut left = true;
-                                for new_col in lower..=upper {
-                                    if left {
-                                        map.insert((search_position.0, new_col), Tile::BoxLeft);
-                                    } else {
-                                        map.insert((search_position.0, new_col), Tile::BoxRight);
-                                    }
-                                    left = !left;
-                                }
-                            } else {
-                                // Move all the boxes in the "move list"
-                                // First, get a list of squares to clear
-                                debug!(len = to_fill.len(), "Moving Box Parts");
-                                let mut to_clear: Vec<(i32, i32)> = Vec::new();
-                                let mut to_fill_values: HashMap<(i32, i32), Tile> = HashMap::new();
-                                for spot in to_fill.iter().cloned() {
-                                    let clear_spot =
-                                        move_direction(&spot, &opposite_direction(&dir));
-                                    to_fill_values.insert(spot, *map.get(&clear_spot).unwrap());
-                                    to_clear.push(clear_spot);
-                                }
-
-                                for clear in to_clear {
-                                    map.insert(clear, Tile::Empty);
-                                }
-
-                                for spot in to_fill {
-                                    map.insert(spot, *to_fill_values.get(&spot).unwrap());
-                                }
-                            }
-                        } else {
-                            map.insert(search_position, Tile::Box);
-                        }
+    if enabled!(Level::DEBUG) {
+        display_map(&maze.walls, &spots);
+    }
+
+    (score, spots)
+}
 
-                        map.insert(test_position, Tile::Empty);
+/// Walls and the tiles on any best path, for drawing.
+pub fn maze_picture(maze: &Maze) -> Grid<usize> {
+    let (_, spots) = best_paths(maze);
+    let mut cells = maze.walls.map(|wall| if *wall { WALL } else { BACKGROUND });
+    for spot in spots {
+        cells[spot] = HIGHLIGHT;
+    }
+    cells
+}
 
-                        player = test_position;
+pub struct Day16;
 
-                        event!(Level::DEBUG, from = ?test_position, to = ?search_position, "Moved Box(s)");
-                    }
+impl Solution for Day16 {
+    type Input = Maze;
 
-                    continue;
-                }
-            },
-        }
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        parse_maze(content)
     }
 
-    display_map_15(&map, rows, cols * 2, player);
-
-    let mut total = 0;
-    for ((r, c), value) in map.iter() {
-        if matches!(value, Tile::Box) {
-            total += 100 * r + c;
-        } else if matches!(value, Tile::BoxLeft) {
-            total += 100 * r + c;
-        }
+    fn part_a(&self, maze: &Self::Input) -> Result<Answer> {
+        let (score, _) = best_paths(maze);
+        Ok(score.context("No path found")?.into())
     }
 
-    info!("Final Score {:?}", total);
+    fn part_b(&self, maze: &Self::Input) -> Result<Answer> {
+        let (_, spots) = best_paths(maze);
+        Ok(spots.len().into())
+    }
 }
 
-#[derive(Clone)]
-struct Robot {
-    pr: i32,
-    pc: i32,
-    vr: i32,
-    vc: i32,
+/// Follow every instruction and return the final GPS score. When `wide` is set every tile of the
+/// layout is doubled in width.
+fn run_warehouse(plan: &RobotPlan, wide: bool) -> Result<i32> {
+    let mut warehouse = Warehouse::new(&plan.layout, wide)?;
+    warehouse.run(&plan.directions);
+    debug!("Final warehouse\n{}", warehouse);
+    Ok(warehouse.gps())
 }
 
-#[instrument]
-pub fn day14(filename: String, rows: i32, cols: i32) {
-    let content = fs::read_to_string(filename).expect("Couldn't read input");
-
-    let mut robots: Vec<Robot> = Vec::new();
-
-    for line in content.lines() {
-        let (pos, velocity) = line.split_once(' ').unwrap();
-
-        let pos = &pos.replace("p=", "");
-        let velocity = &velocity.replace("v=", "");
+pub struct Day15;
 
-        let (pc, pr) = pos.split_once(',').unwrap();
-        let (vc, vr) = velocity.split_once(',').unwrap();
+impl Solution for Day15 {
+    type Input = RobotPlan;
 
-        robots.push(Robot {
-            pr: pr.parse().unwrap(),
-            pc: pc.parse().unwrap(),
-            vr: vr.parse().unwrap(),
-            vc: vc.parse().unwrap(),
-        });
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        RobotPlan::parse(content)
     }
 
-    debug!("Got {:?} Robots", robots.len());
-    debug!("Half Points r={:?} c={:?}", rows / 2, cols / 2);
-
-    let mut factors = vec![0, 0, 0, 0];
-    robots.clone().into_iter().for_each(|robot| {
-        let f_r = (robot.pr + 100 * robot.vr).rem_euclid(rows);
-        let f_c = (robot.pc + 100 * robot.vc).rem_euclid(cols);
-
-        debug!("fr={:?} fc={:?}", f_r, f_c);
-
-        let mut i = 0;
+    fn part_a(&self, plan: &Self::Input) -> Result<Answer> {
+        Ok(run_warehouse(plan, false)?.into())
+    }
 
-        if !(f_r == rows / 2 || f_c == cols / 2) {
-            if f_c > cols / 2 {
-                i = 1;
-            }
+    fn part_b(&self, plan: &Self::Input) -> Result<Answer> {
+        Ok(run_warehouse(plan, true)?.into())
+    }
+}
 
-            if f_r > rows / 2 {
-                i += 2;
-            }
+pub struct Day14 {
+    pub rows: i32,
+    pub cols: i32,
+    pub picture: PictureSearch,
+}
 
-            factors[i] += 1;
-        }
-    });
+impl Solution for Day14 {
+    type Input = Swarm;
 
-    let mut total = 1;
-    debug!("Factors: {:?}", factors);
-    for v in factors {
-        total *= v;
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        Swarm::parse(content, self.rows, self.cols)
     }
 
-    info!("Final Factors {:?}", total);
-
-    let mut display: HashSet<(i32, i32)> = HashSet::new();
-    let mut steps = 0;
-    let mut buffer = String::new();
-    let stdin = io::stdin();
+    fn part_a(&self, swarm: &Self::Input) -> Result<Answer> {
+        Ok(swarm.safety_factor(100).into())
+    }
 
-    while steps < 50000 {
-        /*
-                stdin.read_line(&mut buffer).unwrap();
-                if buffer.trim() == "a" {
-                    break;
-                }
-        */
-        steps += 1;
-        display = HashSet::new();
-        let mut double = false;
-        robots.clone().into_iter().for_each(|robot| {
-            let f_r = (robot.pr + steps * robot.vr).rem_euclid(rows);
-            let f_c = (robot.pc + steps * robot.vc).rem_euclid(cols);
-            if display.contains(&(f_r, f_c)) {
-                double = true;
-            }
-            display.insert((f_r, f_c));
-        });
-        if double {
-            continue;
-        }
-        println!("Step {:?}", steps);
-        for c in 0..cols {
-            for r in 0..rows {
-                if display.contains(&(r, c)) {
-                    print!("#");
-                } else {
-                    print!(".");
-                }
-            }
-            print!("\n");
-        }
+    fn part_b(&self, swarm: &Self::Input) -> Result<Answer> {
+        Ok(swarm.picture(self.picture)?.into())
     }
 }
 
-struct PrizeMatrix {
-    a_x: i64,
-    a_y: i64,
-    b_x: i64,
-    b_y: i64,
-    p_x: i64,
-    p_y: i64,
+pub struct Day13 {
+    pub a_cost: i64,
+    pub b_cost: i64,
+    /// Most presses of each button in part A, 0 for no limit.
+    pub limit: i64,
+    /// Added to the prize coordinates in part B.
+    pub offset: i64,
 }
 
-#[instrument]
-pub fn day13(filename: String) {
-    let content = fs::read_to_string(filename).expect("Couldn't read input");
-
-    let mut machine = PrizeMatrix {
-        a_x: 0,
-        a_y: 0,
-        b_x: 0,
-        b_y: 0,
-        p_x: 0,
-        p_y: 0,
-    };
-    let mut machines: Vec<PrizeMatrix> = Vec::new();
-    for (i, line) in content.lines().enumerate() {
-        match i % 4 {
-            0 => {
-                // Button A Line
-                let matcher = Regex::new(r#"Button A: X\+([0-9]+), Y\+([0-9]+)"#).unwrap();
-                let captures = matcher.captures(line).unwrap();
-                machine.a_x = captures.get(1).unwrap().as_str().parse().unwrap();
-                machine.a_y = captures.get(2).unwrap().as_str().parse().unwrap();
-            }
-            1 => {
-                // Button B Line
-                let matcher = Regex::new(r#"Button B: X\+([0-9]+), Y\+([0-9]+)"#).unwrap();
-                let captures = matcher.captures(line).unwrap();
-                machine.b_x = captures.get(1).unwrap().as_str().parse().unwrap();
-                machine.b_y = captures.get(2).unwrap().as_str().parse().unwrap();
-            }
-            2 => {
-                // Prize Line
-                let matcher = Regex::new(r#"Prize: X=([0-9]+), Y=([0-9]+)"#).unwrap();
-                let captures = matcher.captures(line).unwrap();
-                machine.p_x = captures.get(1).unwrap().as_str().parse().unwrap();
-                machine.p_y = captures.get(2).unwrap().as_str().parse().unwrap();
-            }
-            3 => {
-                // Blank Line
-                machines.push(machine);
-                machine = PrizeMatrix {
-                    a_x: 0,
-                    a_y: 0,
-                    b_x: 0,
-                    b_y: 0,
-                    p_x: 0,
-                    p_y: 0,
-                };
-            }
-            _ => {}
+impl Day13 {
+    fn rules(&self, base: Rules) -> Rules {
+        Rules {
+            a_cost: self.a_cost,
+            b_cost: self.b_cost,
+            ..base
         }
     }
+}
 
-    let mut total: u64 = 0;
-    for (_idx, machine) in machines.iter().enumerate() {
-        let det_denom = machine.a_x * machine.b_y - machine.a_y * machine.b_x;
-        if det_denom == 0 {
-            continue;
-        }
-
-        let mut i: f64 = 0.0;
-        let mut j: f64 = 0.0;
-
-        // Get to 10000000000000 in steps to avoid an overflow
-        /*
-        let i_step = (machine.b_y as f64 * 100000 as f64 - machine.b_x as f64 * 100000 as f64)
-            / det_denom as f64;
-        let j_step = (-machine.a_y as f64 * 100000 as f64 + machine.a_x as f64 * 100000 as f64)
-            / det_denom as f64;
-
-        let add: u64 = 10000000000000 / 100000;
-
-        debug!("Adding {:?} steps of {:?} {:?}", add, i_step, j_step);
-
-        i += add as f64 * i_step;
-        j += add as f64 * j_step;
-        */
-
-        let p_x_a: f64 = (10000000000000.0 + machine.p_x as f64) / det_denom as f64;
-        let p_y_a: f64 = (10000000000000.0 + machine.p_y as f64) / det_denom as f64;
-        i += machine.b_y as f64 * p_x_a - machine.b_x as f64 * p_y_a;
-        j += -machine.a_y as f64 * p_x_a + machine.a_x as f64 * p_y_a;
-
-        if i < 0.0 || j < 0.0 {
-            continue;
-        }
+impl Solution for Day13 {
+    type Input = Vec<ClawMachine>;
 
-        debug!("Prize at {:?} {:?}", i, j);
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        parse_machines(content)
+    }
 
-        let i_rem = (i.round() - i).abs();
-        let j_rem = (j.round() - j).abs();
-        if i_rem < 0.001 && j_rem < 0.001 {
-            total += 3 * (i.round() as u64) + (j.round() as u64);
-        }
+    fn part_a(&self, machines: &Self::Input) -> Result<Answer> {
+        let rules = Rules {
+            press_limit: (self.limit > 0).then_some(self.limit),
+            ..self.rules(Rules::PART_A)
+        };
+        Ok(total_cost(machines, &rules).into())
     }
 
-    info!("Total Presses Needed {:?}", total);
+    fn part_b(&self, machines: &Self::Input) -> Result<Answer> {
+        let rules = Rules {
+            offset: self.offset,
+            ..self.rules(Rules::PART_B)
+        };
+        Ok(total_cost(machines, &rules).into())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -1815,203 +1012,187 @@ struct GardenEntry {
     edges: Vec<Edge>,
 }
 
-#[instrument]
-pub fn day12(filename: String) {
-    let content = fs::read_to_string(filename).expect("Couldn't read input");
-
-    let mut map: HashMap<(i32, i32), char> = HashMap::new();
-    let mut labels: HashMap<(i32, i32), i32> = HashMap::new();
-
-    let mut rows: i32 = 0;
-    let mut cols: i32 = 0;
-
-    for (r, line) in content.lines().enumerate() {
-        rows += 1;
-        cols = line.len() as i32;
-        for (c, char) in line.chars().enumerate() {
-            map.insert((r as i32, c as i32), char);
+/// Number every cell by the region it is in, regions being numbered from 0 in the order they are
+/// first met row by row. Also returns how many regions there are.
+fn label_regions(garden: &Grid<char>) -> (Grid<usize>, usize) {
+    let mut labels: Grid<Option<usize>> = garden.map(|_| None);
+    let mut count = 0;
+    for start in garden.positions() {
+        if labels[start].is_some() {
+            continue;
+        }
+        labels[start] = Some(count);
+        let mut stack = vec![start];
+        while let Some(pos) = stack.pop() {
+            for (_, next) in garden.neighbors(pos) {
+                if garden[next] == garden[pos] && labels[next].is_none() {
+                    labels[next] = Some(count);
+                    stack.push(next);
+                }
+            }
         }
+        count += 1;
     }
+    (labels.map(|l| l.expect("Every cell is in a region")), count)
+}
 
-    let mut sizes: HashMap<i32, GardenEntry> = HashMap::new();
-    let mut label_index: i32 = 0;
-
-    // Two split reigons are not the same reigon
-    for r in 0..rows {
-        for c in 0..cols {
-            let char = map.get(&(r, c)).unwrap();
-            let mut perimiter = 0;
-            let mut label: i32 = *labels.get(&(r, c)).unwrap_or(&label_index);
-            let mut adopted = false;
-
-            let mut edges: Vec<Edge> = Vec::new();
-            for (dr, dc) in LURD {
-                let test = (r + dr, c + dc);
-                let d_type: DirectionType;
-                let other: i32;
-
-                if dr == 0 {
-                    d_type = DirectionType::Vertical;
-                    other = dc;
-                } else {
-                    d_type = DirectionType::Horizontal;
-                    other = dr;
-                }
+/// Split the garden into regions, indexed by their label from `label_regions`.
+fn garden_regions(map: &Grid<char>) -> Vec<GardenEntry> {
+    let (labels, count) = label_regions(map);
+    let mut sizes: Vec<Option<GardenEntry>> = vec![None; count];
+
+    for ((r, c), label) in labels.iter() {
+        let char = &map[(r, c)];
+        let entry = sizes[*label].get_or_insert_with(|| GardenEntry {
+            label: *char,
+            area: 0,
+            perimiter: 0,
+            edges: Vec::new(),
+        });
+        entry.area += 1;
 
-                if let Some(val) = map.get(&test) {
-                    if val != char {
-                        perimiter += 1;
-                        edges.push(Edge {
-                            pos: (r, c),
-                            other,
-                            direction: d_type,
-                        });
-                    } else {
-                        if let Some(test_label_val) = labels.get(&test) {
-                            let test_val: i32 = *test_label_val;
-                            if label != test_val {
-                                // Adopt the existing label, remap everything else.
-                                debug!(
-                                    "Merging {:?} and {:?} which are both {:?}",
-                                    test_val, label, char
-                                );
-                                adopted = true;
-
-                                let mut to_change: Vec<(i32, i32)> = Vec::new();
-                                for (k, v) in labels.iter() {
-                                    if *v == label {
-                                        to_change.push(*k);
-                                    }
-                                }
-
-                                debug!("Changing {:?} labels", to_change.len());
-                                for k in to_change {
-                                    labels.insert(k, test_val);
-                                }
-
-                                // Get the old sizes and merge into the new
-                                let mut size_entry = sizes
-                                    .get(&label)
-                                    .unwrap_or(&GardenEntry {
-                                        label: '#',
-                                        area: 0,
-                                        perimiter: 0,
-                                        edges: Vec::new(),
-                                    })
-                                    .clone();
-                                sizes
-                                    .entry(test_val)
-                                    .and_modify(|x| {
-                                        x.area += size_entry.area;
-                                        x.perimiter += size_entry.perimiter;
-                                        x.edges.append(&mut size_entry.edges);
-                                    })
-                                    .or_insert(size_entry);
-                                sizes.remove(&label);
-
-                                label = test_val;
-                            }
-                        }
-                    }
-                } else {
-                    perimiter += 1;
-                    edges.push(Edge {
-                        pos: (r, c),
-                        other,
-                        direction: d_type,
-                    });
-                }
+        for d in Direction::CARDINAL {
+            let test = move_direction(&(r, c), &d);
+            if map.get(test) == Some(char) {
+                continue;
             }
 
-            sizes
-                .entry(label)
-                .and_modify(|x| {
-                    x.area += 1;
-                    x.perimiter += perimiter;
-                    x.edges.append(&mut edges);
-                })
-                .or_insert(GardenEntry {
-                    label: *char,
-                    area: 1,
-                    perimiter,
-                    edges,
-                });
+            let (dr, dc) = d.offset();
+            let (direction, other) = if dr == 0 {
+                (DirectionType::Vertical, dc)
+            } else {
+                (DirectionType::Horizontal, dr)
+            };
+            entry.perimiter += 1;
+            entry.edges.push(Edge {
+                pos: (r, c),
+                other,
+                direction,
+            });
+        }
+    }
 
-            labels.insert((r, c), label);
+    sizes
+        .into_iter()
+        .map(|s| s.expect("Every region has a cell"))
+        .collect()
+}
 
-            if !adopted {
-                label_index += 1;
+/// Every cell coloured by the region it is in, for drawing with a palette of `colours`. Regions
+/// take the colours after the reserved ones, each picking the first that none of the regions
+/// already coloured next to it has, so neighbours only share a colour when the palette runs out.
+pub fn garden_picture(garden: &Grid<char>, colours: usize) -> Grid<usize> {
+    let (regions, count) = label_regions(garden);
+
+    let mut adjacent: Vec<HashSet<usize>> = vec![HashSet::new(); count];
+    for (pos, region) in regions.iter() {
+        for (_, next) in regions.neighbors(pos) {
+            if regions[next] != *region {
+                adjacent[*region].insert(regions[next]);
             }
         }
     }
 
-    let mut total = 0;
-    for (char, entry) in sizes.iter() {
-        debug!(
-            "Reigon {:?} area={:?}, perimiter={:?}",
-            char, entry.area, entry.perimiter
-        );
-        total += entry.area * entry.perimiter;
+    let available = colours.saturating_sub(HIGHLIGHT).max(1);
+    let mut region_colours: Vec<Option<usize>> = vec![None; count];
+    for region in 0..count {
+        let taken: HashSet<usize> = adjacent[region]
+            .iter()
+            .filter_map(|r| region_colours[*r])
+            .collect();
+        let colour = (HIGHLIGHT..HIGHLIGHT + available)
+            .find(|c| !taken.contains(c))
+            .unwrap_or(HIGHLIGHT + region % available);
+        region_colours[region] = Some(colour);
     }
+    regions.map(|r| region_colours[*r].expect("Every region is coloured"))
+}
 
-    info!("Total Fence Cost {:?}", total);
-
-    let mut part_b_price = 0;
-
-    for (_, entry) in sizes.iter() {
-        let mut total_edges = 0;
-        debug!("Total Edges To Check {:?}", entry.edges.len());
-        debug!("{:?}", entry.edges);
-        for other in [-1, 1] {
-            // Process Vertial Edges
-            for c in 0..cols {
-                let mut filtered_edges: Vec<&Edge> = entry
-                    .edges
-                    .iter()
-                    .filter(|x| {
-                        (x.pos.1 == c)
-                            && (other == x.other)
-                            && matches!(x.direction, DirectionType::Vertical)
-                    })
-                    .collect();
-                filtered_edges.sort_by(|x, y| x.pos.0.cmp(&y.pos.0));
-                let mut previous_r = -2;
-                for e in filtered_edges {
-                    if e.pos.0 - previous_r > 1 {
-                        total_edges += 1;
+pub struct Day12;
+
+impl Solution for Day12 {
+    type Input = Grid<char>;
+
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        Grid::parse_chars(content)
+    }
+
+    fn part_a(&self, garden: &Self::Input) -> Result<Answer> {
+        let sizes = garden_regions(garden);
+
+        let mut total = 0;
+        for entry in &sizes {
+            debug!(
+                "Reigon {:?} area={:?}, perimiter={:?}",
+                entry.label, entry.area, entry.perimiter
+            );
+            total += entry.area * entry.perimiter;
+        }
+
+        Ok(total.into())
+    }
+
+    fn part_b(&self, garden: &Self::Input) -> Result<Answer> {
+        let sizes = garden_regions(garden);
+        let (rows, cols) = (garden.rows(), garden.cols());
+
+        let mut part_b_price = 0;
+
+        for entry in &sizes {
+            let mut total_edges = 0;
+            debug!("Total Edges To Check {:?}", entry.edges.len());
+            debug!("{:?}", entry.edges);
+            for other in [-1, 1] {
+                // Process Vertial Edges
+                for c in 0..cols {
+                    let mut filtered_edges: Vec<&Edge> = entry
+                        .edges
+                        .iter()
+                        .filter(|x| {
+                            (x.pos.1 == c)
+                                && (other == x.other)
+                                && matches!(x.direction, DirectionType::Vertical)
+                        })
+                        .collect();
+                    filtered_edges.sort_by_key(|x| x.pos.0);
+                    let mut previous_r = -2;
+                    for e in filtered_edges {
+                        if e.pos.0 - previous_r > 1 {
+                            total_edges += 1;
+                        }
+                        previous_r = e.pos.0;
                     }
-                    previous_r = e.pos.0;
                 }
-            }
-            // Process Horizontal Edges
-            for r in 0..rows {
-                let mut filtered_edges: Vec<&Edge> = entry
-                    .edges
-                    .iter()
-                    .filter(|x| {
-                        (x.pos.0 == r)
-                            && (other == x.other)
-                            && matches!(x.direction, DirectionType::Horizontal)
-                    })
-                    .collect();
-                filtered_edges.sort_by(|x, y| x.pos.1.cmp(&y.pos.1));
-                let mut previous_c = -2;
-                for e in filtered_edges {
-                    if e.pos.1 - previous_c > 1 {
-                        total_edges += 1;
+                // Process Horizontal Edges
+                for r in 0..rows {
+                    let mut filtered_edges: Vec<&Edge> = entry
+                        .edges
+                        .iter()
+                        .filter(|x| {
+                            (x.pos.0 == r)
+                                && (other == x.other)
+                                && matches!(x.direction, DirectionType::Horizontal)
+                        })
+                        .collect();
+                    filtered_edges.sort_by_key(|x| x.pos.1);
+                    let mut previous_c = -2;
+                    for e in filtered_edges {
+                        if e.pos.1 - previous_c > 1 {
+                            total_edges += 1;
+                        }
+                        previous_c = e.pos.1;
                     }
-                    previous_c = e.pos.1;
                 }
             }
+            debug!(
+                "Reigon {:?} area={:?}, total_edges={:?}",
+                entry.label, entry.area, total_edges
+            );
+            part_b_price += total_edges * entry.area;
         }
-        debug!(
-            "Reigon {:?} area={:?}, total_edges={:?}",
-            entry.label, entry.area, total_edges
-        );
-        part_b_price += total_edges * entry.area;
-    }
 
-    info!("Total Fence Cost B {:?}", part_b_price);
+        Ok(part_b_price.into())
+    }
 }
 
 #[instrument(skip(cache))]
@@ -2027,7 +1208,7 @@ pub fn split_stones(stone: u64, n: u64, cache: &mut HashMap<(u64, u64), u64>) ->
                 cache.insert((1, n - 1), val);
                 return val;
             }
-        } else if stone.to_string().len() % 2 == 0 {
+        } else if stone.to_string().len().is_multiple_of(2) {
             let mut stone_string = stone.to_string();
             let a = stone_string.split_off(stone_string.len() / 2);
 
@@ -2058,69 +1239,59 @@ pub fn split_stones(stone: u64, n: u64, cache: &mut HashMap<(u64, u64), u64>) ->
     }
 }
 
-#[instrument]
-pub fn day11(filename: String) {
-    let content = fs::read_to_string(filename).expect("Couldn't read input");
-
-    let mut stones: Vec<u64> = content
-        .trim()
-        .split(' ')
-        .map(|x| x.parse().unwrap())
-        .collect();
-
-    stones.sort();
-
+/// Number of stones after blinking `n` times.
+fn blink(stones: &[u64], n: u64) -> u64 {
     // Maps (current_value, n_steps) -> Number of Stones
     let mut cache: HashMap<(u64, u64), u64> = HashMap::new();
     let mut total = 0;
 
-    for i in 0..100 {
-        for j in 1..75 {
-            split_stones(i, j, &mut cache);
-        }
-    }
-
-    for stone in &stones {
-        total += split_stones(*stone, 75, &mut cache);
+    for stone in stones {
+        total += split_stones(*stone, n, &mut cache);
         debug!("Finished {:?} Cache Size {:?}", stone, cache.len());
     }
-    info!("Final Stones Recursive {:?}", total);
+
+    total
 }
 
-pub const LURD: [(i32, i32); 4] = [(-1, 0), (1, 0), (0, 1), (0, -1)];
+pub struct Day11;
+
+impl Solution for Day11 {
+    type Input = Vec<u64>;
+
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        let mut stones: Vec<u64> = content
+            .split_whitespace()
+            .map(|x| x.parse())
+            .collect::<Result<_, _>>()?;
+
+        stones.sort();
+
+        Ok(stones)
+    }
+
+    fn part_a(&self, stones: &Self::Input) -> Result<Answer> {
+        Ok(blink(stones, 25).into())
+    }
+
+    fn part_b(&self, stones: &Self::Input) -> Result<Answer> {
+        Ok(blink(stones, 75).into())
+    }
+}
 
 struct Step {
     pos: (i32, i32),
     val: i32,
 }
 
-#[instrument]
-pub fn day10(filename: String) {
-    let content = fs::read_to_string(filename).expect("Couldn't read input");
-
-    let mut starts: Vec<(i32, i32)> = Vec::new();
-    let mut map: HashMap<(i32, i32), i32> = HashMap::new();
-
-    for (r, line) in content.lines().enumerate() {
-        for (c, char) in line.chars().enumerate() {
-            let parsed: i32 = char.to_string().parse().unwrap();
-            map.insert((r as i32, c as i32), parsed);
-            if parsed == 0 {
-                starts.push((r as i32, c as i32));
-            }
-        }
-    }
-
+/// Sum of trailhead scores and sum of trailhead ratings.
+fn trailheads(map: &Grid<i32>) -> (usize, usize) {
     let mut score = 0;
     let mut routes = 0;
-    debug!("Got {:?} Trailheds to Check", starts.len());
-    for start in starts.iter() {
+    debug!("Got {:?} Trailheds to Check", map.find_all(&0).count());
+    for start in map.find_all(&0) {
         let mut to_check: Vec<Step> = Vec::new();
         let mut end_points: HashSet<(i32, i32)> = HashSet::new();
-        to_check.push(Step {
-            pos: *start,
-            val: 0,
-        });
+        to_check.push(Step { pos: start, val: 0 });
 
         while let Some(x) = to_check.pop() {
             if x.val == 9 {
@@ -2129,12 +1300,10 @@ pub fn day10(filename: String) {
                 continue;
             }
 
-            for (dr, dc) in LURD {
-                let test = (x.pos.0 + dr, x.pos.1 + dc);
-                if let Some(v) = map.get(&test) {
-                    if *v == x.val + 1 {
-                        to_check.push(Step { pos: test, val: *v });
-                    }
+            for (_, test) in map.neighbors(x.pos) {
+                let v = map[test];
+                if v == x.val + 1 {
+                    to_check.push(Step { pos: test, val: v });
                 }
             }
         }
@@ -2142,12 +1311,34 @@ pub fn day10(filename: String) {
         score += end_points.len();
     }
 
-    info!("Final Score {:?}", score);
-    info!("Final Rating {:?}", routes);
+    (score, routes)
+}
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    type Input = Grid<i32>;
+
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        Grid::parse(
+            content,
+            |c| Ok(c.to_digit(10).context("Bad height")? as i32),
+        )
+    }
+
+    fn part_a(&self, topo: &Self::Input) -> Result<Answer> {
+        let (score, _) = trailheads(topo);
+        Ok(score.into())
+    }
+
+    fn part_b(&self, topo: &Self::Input) -> Result<Answer> {
+        let (_, routes) = trailheads(topo);
+        Ok(routes.into())
+    }
 }
 
 #[derive(Debug, Clone)]
-struct Chunk {
+pub struct Chunk {
     start: usize,
     id: i64,
     length: usize,
@@ -2183,7 +1374,7 @@ pub fn defrag(chunks: Vec<Chunk>) -> Vec<Chunk> {
                     break;
                 }
             }
-            chunky.sort_by(|a, b| a.start.cmp(&b.start));
+            chunky.sort_by_key(|a| a.start);
             if !set {
                 known -= 1;
             }
@@ -2192,130 +1383,126 @@ pub fn defrag(chunks: Vec<Chunk>) -> Vec<Chunk> {
         }
     }
 
-    chunky.sort_by(|a, b| a.start.cmp(&b.start));
+    chunky.sort_by_key(|a| a.start);
     chunky
 }
 
-#[instrument]
-pub fn day9(filename: String) {
-    let content = fs::read_to_string(filename).expect("Couldn't read input");
+pub struct Day9;
 
-    let mut chunks: Vec<Chunk> = Vec::new();
-    let mut id = 0;
-    let mut offset = 0;
-    let mut is_file = true;
+impl Solution for Day9 {
+    type Input = Vec<Chunk>;
 
-    for c in content.chars() {
-        if c == '\n' {
-            break;
-        }
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        let mut chunks: Vec<Chunk> = Vec::new();
+        let mut id = 0;
+        let mut offset = 0;
+        let mut is_file = true;
+
+        for c in content.chars() {
+            if c == '\n' {
+                break;
+            }
 
-        let size: usize = c.to_string().parse().unwrap();
-        if is_file {
-            if size > 0 {
-                chunks.push(Chunk {
-                    start: offset,
-                    id: id,
-                    length: size,
-                });
-                id += 1;
+            let size = c.to_digit(10).context("Bad block size")? as usize;
+            if is_file {
+                if size > 0 {
+                    chunks.push(Chunk {
+                        start: offset,
+                        id,
+                        length: size,
+                    });
+                    id += 1;
+                    offset += size;
+                }
+                is_file = false;
+            } else {
                 offset += size;
+                is_file = true;
             }
-            is_file = false;
-        } else {
-            offset += size;
-            is_file = true;
         }
-    }
 
-    let mut checksum: u64 = 0;
+        Ok(chunks)
+    }
 
-    let mut cursor_left = 0;
-    let mut offset_left: usize = 0;
+    fn part_a(&self, chunks: &Self::Input) -> Result<Answer> {
+        let mut checksum: u64 = 0;
 
-    let mut cursor_right = chunks[chunks.len() - 1].start + chunks[chunks.len() - 1].length - 1;
-    let mut offset_right: usize = 0;
+        let mut cursor_left = 0;
+        let mut offset_left: usize = 0;
 
-    loop {
-        let current = chunks[offset_left].clone();
-
-        // Are we in current or between current and next
-        if cursor_left < current.start + current.length {
-            // Still in current
-            let add = cursor_left as u64 * current.id as u64;
-            checksum += add;
-            debug!(current.id, cursor_left, add, checksum);
-            cursor_left += 1;
-        } else {
-            if offset_left + 1 == chunks.len() {
-                break;
-            }
-            let next = chunks[&offset_left + 1].clone();
-            if cursor_left < next.start {
-                // In a gap
+        let mut cursor_right = chunks[chunks.len() - 1].start + chunks[chunks.len() - 1].length - 1;
+        let mut offset_right: usize = 0;
 
-                let back_current = chunks[chunks.len() - 1 - offset_right].clone();
+        loop {
+            let current = chunks[offset_left].clone();
 
-                let add = cursor_left as u64 * back_current.id as u64;
+            // Are we in current or between current and next
+            if cursor_left < current.start + current.length {
+                // Still in current
+                let add = cursor_left as u64 * current.id as u64;
                 checksum += add;
-                debug!(back_current.id, cursor_left, add, checksum);
+                debug!(current.id, cursor_left, add, checksum);
+                cursor_left += 1;
+            } else {
+                if offset_left + 1 == chunks.len() {
+                    break;
+                }
+                let next = chunks[&offset_left + 1].clone();
+                if cursor_left < next.start {
+                    // In a gap
 
-                cursor_right -= 1;
+                    let back_current = chunks[chunks.len() - 1 - offset_right].clone();
+
+                    let add = cursor_left as u64 * back_current.id as u64;
+                    checksum += add;
+                    debug!(back_current.id, cursor_left, add, checksum);
+
+                    cursor_right -= 1;
+
+                    if cursor_right < back_current.start {
+                        let back_next = chunks[chunks.len() - 2 - offset_right].clone();
+                        cursor_right = back_next.start + back_next.length - 1;
+                        offset_right += 1;
+                    }
 
-                if cursor_right < back_current.start {
-                    let back_next = chunks[chunks.len() - 2 - offset_right].clone();
-                    cursor_right = back_next.start + back_next.length - 1;
-                    offset_right += 1;
+                    cursor_left += 1;
                 }
 
-                cursor_left += 1;
+                if cursor_left == next.start {
+                    offset_left += 1;
+                }
             }
 
-            if cursor_left == next.start {
-                offset_left += 1;
+            // If left passed right, break
+            if cursor_left > cursor_right {
+                break;
             }
         }
 
-        // If left passed right, break
-        if cursor_left > cursor_right {
-            break;
-        }
+        Ok(checksum.into())
     }
 
-    info!("Total Checksum {:?}", checksum);
+    fn part_b(&self, chunks: &Self::Input) -> Result<Answer> {
+        let chunks = defrag(chunks.clone());
+        debug!("Defragment Complete");
 
-    chunks = defrag(chunks);
-    info!("Defragment Complete");
-
-    let mut checksum_two: u64 = 0;
-    for chunk in chunks {
-        for p in chunk.start..chunk.start + chunk.length {
-            checksum_two += p as u64 * chunk.id as u64;
+        let mut checksum_two: u64 = 0;
+        for chunk in chunks {
+            for p in chunk.start..chunk.start + chunk.length {
+                checksum_two += p as u64 * chunk.id as u64;
+            }
         }
-    }
 
-    info!("Total Checksum B {:?}", checksum_two);
+        Ok(checksum_two.into())
+    }
 }
 
-#[instrument]
-pub fn day8(filename: String) {
-    let content = fs::read_to_string(filename).expect("Couldn't read input");
-
+/// Antinodes from pairs of antennas, without and with resonant harmonics.
+fn antinodes(grid: &Grid<char>) -> (HashSet<Position>, HashSet<Position>) {
     let mut nodes: HashMap<char, Vec<(i32, i32)>> = HashMap::new();
-    let mut rows: i32 = 0;
-    let mut cols: i32 = 0;
-
-    for (row, line) in content.lines().enumerate() {
-        cols = line.len() as i32;
-        rows += 1;
-        for (col, c) in line.chars().enumerate() {
-            if c == '.' {
-                continue;
-            }
-            nodes
-                .entry(c)
-                .and_modify(|x| x.push((row as i32, col as i32)))
-                .or_insert(vec![(row as i32, col as i32)]);
+    for (pos, c) in grid.iter() {
+        if *c != '.' {
+            nodes.entry(*c).or_default().push(pos);
         }
     }
 
@@ -2330,7 +1517,7 @@ pub fn day8(filename: String) {
                 }
                 let x = 2 * c - a;
                 let y = 2 * d - b;
-                if is_in_bounds(rows, cols, x, y) {
+                if grid.in_bounds((x, y)) {
                     //debug!("Found {:?}", (x, y));
                     antinodes.insert((x, y));
                 }
@@ -2338,10 +1525,10 @@ pub fn day8(filename: String) {
                 let dx = c - a;
                 let dy = d - b;
 
-                for copy in 1..rows {
+                for copy in 1..grid.rows() {
                     let x = a + copy * dx;
                     let y = b + copy * dy;
-                    if is_in_bounds(rows, cols, x, y) {
+                    if grid.in_bounds((x, y)) {
                         //debug!("Found {:?}", (x, y));
                         antinodes_all.insert((x, y));
                     }
@@ -2350,13 +1537,32 @@ pub fn day8(filename: String) {
         }
     }
 
-    info!("Total Antinodes {:?}", antinodes.len());
-    info!("Total Antinodes B {:?}", antinodes_all.len());
+    (antinodes, antinodes_all)
+}
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    type Input = Grid<char>;
+
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        Grid::parse_chars(content)
+    }
+
+    fn part_a(&self, antennas: &Self::Input) -> Result<Answer> {
+        let (antinodes, _) = antinodes(antennas);
+        Ok(antinodes.len().into())
+    }
+
+    fn part_b(&self, antennas: &Self::Input) -> Result<Answer> {
+        let (_, antinodes_all) = antinodes(antennas);
+        Ok(antinodes_all.len().into())
+    }
 }
 
 #[instrument]
 pub fn try_math(target: i64, current: i64, parts: &[i64], can_concat: bool) -> bool {
-    if parts.len() == 0 {
+    if parts.is_empty() {
         return false;
     }
 
@@ -2386,33 +1592,47 @@ pub fn try_math(target: i64, current: i64, parts: &[i64], can_concat: bool) -> b
     }
 }
 
-#[instrument]
-pub fn day7(filename: String) {
-    let content = fs::read_to_string(filename).expect("Couldn't read input");
-
-    let mut inputs: Vec<(i64, Vec<i64>)> = Vec::new();
-
-    for line in content.lines() {
-        let (target, rest) = line.split_once(": ").unwrap();
-        let parts: Vec<i64> = rest.split(' ').map(|x| x.parse().unwrap()).collect();
-
-        inputs.push((target.parse().unwrap(), parts));
-    }
-
+/// Sum of the targets that can be made from their parts, optionally allowing concatenation.
+fn calibration_total(inputs: &[(i64, Vec<i64>)], can_concat: bool) -> i64 {
     let mut total = 0;
-    let mut total_b = 0;
 
     for (target, parts) in inputs {
-        if try_math(target, parts[0], &parts[1..], false) {
+        if try_math(*target, parts[0], &parts[1..], can_concat) {
             total += target;
         }
-        if try_math(target, parts[0], &parts[1..], true) {
-            total_b += target;
+    }
+
+    total
+}
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    type Input = Vec<(i64, Vec<i64>)>;
+
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        let mut inputs: Vec<(i64, Vec<i64>)> = Vec::new();
+
+        for line in content.lines() {
+            let (target, rest) = line.split_once(": ").context("Bad equation")?;
+            let parts: Vec<i64> = rest
+                .split(' ')
+                .map(|x| x.parse())
+                .collect::<Result<_, _>>()?;
+
+            inputs.push((target.parse()?, parts));
         }
+
+        Ok(inputs)
+    }
+
+    fn part_a(&self, inputs: &Self::Input) -> Result<Answer> {
+        Ok(calibration_total(inputs, false).into())
     }
 
-    info!("Final Total {:?}", total);
-    info!("Final Total B {:?}", total_b);
+    fn part_b(&self, inputs: &Self::Input) -> Result<Answer> {
+        Ok(calibration_total(inputs, true).into())
+    }
 }
 
 #[derive(Eq, Hash, PartialEq, Clone)]
@@ -2421,37 +1641,18 @@ struct Bearing {
     d: Direction,
 }
 
-#[instrument]
-pub fn day6(filename: String) {
-    let content = fs::read_to_string(filename).expect("Couldn't read input");
-
-    let mut map: HashSet<(i32, i32)> = HashSet::new();
-    let mut rows: i32 = 0;
-    let mut cols: i32 = 0;
-
-    let mut position: Bearing = Bearing {
-        pos: (-1, -1),
-        d: Direction::North,
-    };
+pub struct Lab {
+    /// Set where there is an obstruction.
+    map: Grid<bool>,
+    start: Bearing,
+}
 
-    for line in content.lines() {
-        cols = line.len() as i32;
-        for (i, c) in line.chars().enumerate() {
-            match c {
-                '#' => {
-                    map.insert((rows, i as i32));
-                }
-                '^' => {
-                    position = Bearing {
-                        pos: (rows, i as i32),
-                        d: Direction::North,
-                    };
-                }
-                _ => {}
-            };
-        }
-        rows += 1;
-    }
+/// Walk the guard out of the lab, returning the visited positions and, when `find_loops` is set,
+/// every position where a new obstruction would trap the guard in a loop.
+fn patrol(lab: &Lab, find_loops: bool) -> (HashSet<Position>, HashSet<Position>) {
+    let Lab { map, start } = lab;
+    let blocked = |pos: Position| map.get(pos).copied().unwrap_or(false);
+    let mut position = start.clone();
 
     let mut visited_pos: HashSet<(i32, i32)> = HashSet::new();
 
@@ -2462,36 +1663,34 @@ pub fn day6(filename: String) {
         let mut new_pos = move_direction(&position.pos, &position.d);
 
         let new_direction: Direction;
-        if map.contains(&new_pos) {
+        if blocked(new_pos) {
             // Turn Right but don't move
             new_direction = turn(&position.d, Rotation::Right);
-            new_pos = position.pos.clone();
+            new_pos = position.pos;
         } else {
             new_direction = position.d.clone();
 
-            if !is_in_bounds(rows, cols, position.pos.0, position.pos.1) {
+            if !map.in_bounds(position.pos) {
                 break;
             }
 
             let fake_blocker = move_direction(&position.pos, &position.d);
 
-            if is_in_bounds(rows, cols, fake_blocker.0, fake_blocker.1)
-                && !visited_pos.contains(&fake_blocker)
-            {
+            if find_loops && map.in_bounds(fake_blocker) && !visited_pos.contains(&fake_blocker) {
                 let mut test_bearing = Bearing {
-                    pos: position.pos.clone(),
+                    pos: position.pos,
                     d: turn(&position.d, Rotation::Right),
                 };
                 // Need to store the extra locations
                 let mut test_visited: HashSet<Bearing> = HashSet::new();
                 loop {
                     // If OOB Break
-                    if !is_in_bounds(rows, cols, test_bearing.pos.0, test_bearing.pos.1) {
+                    if !map.in_bounds(test_bearing.pos) {
                         break;
                     }
 
                     if test_visited.contains(&test_bearing) {
-                        additions.insert(fake_blocker.clone());
+                        additions.insert(fake_blocker);
                         break;
                     }
 
@@ -2499,7 +1698,7 @@ pub fn day6(filename: String) {
 
                     let test_pos = move_direction(&test_bearing.pos, &test_bearing.d);
 
-                    if map.contains(&test_pos) || test_pos == fake_blocker {
+                    if blocked(test_pos) || test_pos == fake_blocker {
                         test_bearing.d = turn(&test_bearing.d, Rotation::Right);
                     } else {
                         test_bearing.pos = test_pos;
@@ -2508,7 +1707,7 @@ pub fn day6(filename: String) {
             }
         }
 
-        visited_pos.insert(position.pos.clone());
+        visited_pos.insert(position.pos);
 
         position = Bearing {
             pos: new_pos,
@@ -2516,84 +1715,141 @@ pub fn day6(filename: String) {
         };
     }
 
-    info!("Total Visited Locations {:?}", visited_pos.len());
-    info!("Total Potential Additions {:?}", additions.len());
+    (visited_pos, additions)
 }
 
-pub fn day5() {
-    let content = fs::read_to_string("./inputs/day5.txt").expect("Couldn't read input");
+pub struct Day6;
 
-    // Constraints in the form X|Y
-    // <blank line>
-    // Comma Separated Values
-    //
-    // X must be left of Y in the lists.
-    // i.e. Fail if Y is right of X
-    // Store a list of all values not allowed to be right of X
+impl Solution for Day6 {
+    type Input = Lab;
 
-    let mut constraints: HashMap<i32, Vec<i32>> = HashMap::new();
-    let mut lists: Vec<Vec<i32>> = Vec::new();
-    let mut parsing_contstraints = true;
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        let chars = Grid::parse_chars(content)?;
+        let pos = chars.find(&'^').context("No guard in the lab")?;
 
-    for line in content.lines() {
-        if line.len() == 0 {
-            parsing_contstraints = false;
-            continue;
-        }
+        Ok(Lab {
+            map: chars.map(|c| *c == '#'),
+            start: Bearing {
+                pos,
+                d: Direction::North,
+            },
+        })
+    }
 
-        if parsing_contstraints {
-            let (a, b) = line.split_once('|').unwrap();
-            let a_p: i32 = a.parse().unwrap();
-            let b_p: i32 = b.parse().unwrap();
+    fn part_a(&self, lab: &Self::Input) -> Result<Answer> {
+        let (visited_pos, _) = patrol(lab, false);
+        Ok(visited_pos.len().into())
+    }
 
-            constraints
-                .entry(a_p)
-                .and_modify(|v| v.push(b_p))
-                .or_insert(vec![b_p]);
-        } else {
-            lists.push(line.split(',').map(|x| x.parse().unwrap()).collect());
-        }
+    fn part_b(&self, lab: &Self::Input) -> Result<Answer> {
+        let (_, additions) = patrol(lab, true);
+        Ok(additions.len().into())
     }
+}
 
-    let mut total = 0;
-    let mut b_total = 0;
-
-    for list in lists {
-        let mut clean = true;
-        for (i, item) in list.iter().enumerate() {
-            let d = constraints.get(&item);
-            if let Some(right) = d {
-                for check in right {
-                    if list[..i].contains(check) {
-                        clean = false;
-                        break;
-                    }
+pub struct PrintQueue {
+    constraints: HashMap<i32, Vec<i32>>,
+    lists: Vec<Vec<i32>>,
+}
+
+/// Check that no page in the list appears after a page that must come before it.
+fn is_ordered(list: &[i32], constraints: &HashMap<i32, Vec<i32>>) -> bool {
+    for (i, item) in list.iter().enumerate() {
+        if let Some(right) = constraints.get(item) {
+            for check in right {
+                if list[..i].contains(check) {
+                    return false;
                 }
             }
-            if !clean {
-                break;
+        }
+    }
+
+    true
+}
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    type Input = PrintQueue;
+
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        // Constraints in the form X|Y
+        // <blank line>
+        // Comma Separated Values
+        //
+        // X must be left of Y in the lists.
+        // i.e. Fail if Y is right of X
+        // Store a list of all values not allowed to be right of X
+
+        let mut constraints: HashMap<i32, Vec<i32>> = HashMap::new();
+        let mut lists: Vec<Vec<i32>> = Vec::new();
+        let mut parsing_contstraints = true;
+
+        for line in content.lines() {
+            if line.is_empty() {
+                parsing_contstraints = false;
+                continue;
+            }
+
+            if parsing_contstraints {
+                let (a, b) = line.split_once('|').context("Bad constraint")?;
+                let a_p: i32 = a.parse()?;
+                let b_p: i32 = b.parse()?;
+
+                constraints
+                    .entry(a_p)
+                    .and_modify(|v| v.push(b_p))
+                    .or_insert(vec![b_p]);
+            } else {
+                lists.push(
+                    line.split(',')
+                        .map(|x| x.parse())
+                        .collect::<Result<_, _>>()?,
+                );
             }
         }
-        if clean {
-            let half = (list.len() as f32 / 2.0).floor();
-            total += list[half as usize];
-        } else {
+
+        Ok(PrintQueue { constraints, lists })
+    }
+
+    fn part_a(&self, queue: &Self::Input) -> Result<Answer> {
+        let mut total = 0;
+
+        for list in queue.lists.iter() {
+            if is_ordered(list, &queue.constraints) {
+                let half = (list.len() as f32 / 2.0).floor();
+                total += list[half as usize];
+            }
+        }
+
+        Ok(total.into())
+    }
+
+    fn part_b(&self, queue: &Self::Input) -> Result<Answer> {
+        let constraints = &queue.constraints;
+        let mut b_total = 0;
+
+        for list in queue.lists.iter() {
+            if is_ordered(list, constraints) {
+                continue;
+            }
+
             let mut sorted = list.clone();
             sorted.sort_by(|a, b| {
                 // Check for Greater
                 let d = constraints.get(a);
-                if let Some(right) = d {
-                    if right.contains(b) {
-                        return Ordering::Greater;
-                    }
+                if let Some(right) = d
+                    && right.contains(b)
+                {
+                    return Ordering::Greater;
                 }
 
                 // Check for Lesser
                 let d = constraints.get(b);
-                if let Some(right) = d {
-                    if right.contains(a) {
-                        return Ordering::Less;
-                    }
+                if let Some(right) = d
+                    && right.contains(a)
+                {
+                    return Ordering::Less;
                 }
 
                 Ordering::Equal
@@ -2601,151 +1857,104 @@ pub fn day5() {
             let half = (sorted.len() as f32 / 2.0).floor();
             b_total += sorted[half as usize];
         }
-    }
 
-    info!("Final Total Part A {:?}", total);
-    info!("Final Total Part B {:?}", b_total);
+        Ok(b_total.into())
+    }
 }
 
 struct XmasBit {
     c: char,
-    row: i32,
-    col: i32,
-    dr: i32,
-    dc: i32,
-}
-
-pub fn day4() {
-    let content = fs::read_to_string("./inputs/day4.txt").expect("Couldn't read input");
-
-    let mut lines: Vec<Vec<char>> = Vec::new();
-    let mut to_check: Vec<XmasBit> = Vec::new();
-    let mut to_check_b: Vec<XmasBit> = Vec::new();
-    let mut rows = 0;
-    let mut cols = 0;
-
-    let rcs = vec![
-        (-1, -1),
-        (-1, 0),
-        (-1, 1),
-        (0, 1),
-        (0, -1),
-        (1, 1),
-        (1, 0),
-        (1, -1),
-    ];
+    pos: Position,
+    d: Direction,
+}
 
-    for line in content.lines() {
-        cols = 0;
-        lines.push(
-            line.chars()
-                .map(|x| {
-                    if x == 'X' {
-                        rcs.iter().for_each(|e| {
-                            let (dr, dc) = e;
-                            to_check.push(XmasBit {
-                                c: 'X',
-                                row: rows,
-                                col: cols,
-                                dr: *dr,
-                                dc: *dc,
-                            });
-                        });
-                    } else if x == 'A' {
-                        to_check_b.push(XmasBit {
-                            c: 'A',
-                            row: rows,
-                            col: cols,
-                            dr: 0,
-                            dc: 0,
-                        });
-                    }
-                    cols += 1;
-                    x
-                })
-                .collect(),
-        );
-        rows += 1;
-    }
+pub struct Day4;
 
-    let mut found = 0;
+impl Solution for Day4 {
+    type Input = Grid<char>;
 
-    while let Some(elem) = to_check.pop() {
-        // Check Bounds
-        let new_r = elem.row + elem.dr;
-        let new_c = elem.col + elem.dc;
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        Grid::parse_chars(content)
+    }
 
-        if !is_in_bounds(rows, cols, new_r, new_c) {
-            continue;
+    fn part_a(&self, grid: &Self::Input) -> Result<Answer> {
+        let mut to_check: Vec<XmasBit> = Vec::new();
+
+        for pos in grid.find_all(&'X') {
+            for d in Direction::ALL {
+                to_check.push(XmasBit { c: 'X', pos, d });
+            }
         }
 
-        // Check Letter
-        let letter = lines[new_r as usize][new_c as usize];
-        match letter {
-            'M' => {
-                if elem.c == 'X' {
-                    to_check.push(XmasBit {
-                        c: 'M',
-                        row: new_r,
-                        col: new_c,
-                        dr: elem.dr,
-                        dc: elem.dc,
-                    });
+        let mut found = 0;
+
+        while let Some(elem) = to_check.pop() {
+            let new_pos = move_direction(&elem.pos, &elem.d);
+
+            // Check Letter, out of bounds is never part of the word
+            let Some(letter) = grid.get(new_pos) else {
+                continue;
+            };
+            match letter {
+                'M' => {
+                    if elem.c == 'X' {
+                        to_check.push(XmasBit {
+                            c: 'M',
+                            pos: new_pos,
+                            d: elem.d,
+                        });
+                    }
                 }
-            }
-            'A' => {
-                if elem.c == 'M' {
-                    to_check.push(XmasBit {
-                        c: 'A',
-                        row: new_r,
-                        col: new_c,
-                        dr: elem.dr,
-                        dc: elem.dc,
-                    });
+                'A' => {
+                    if elem.c == 'M' {
+                        to_check.push(XmasBit {
+                            c: 'A',
+                            pos: new_pos,
+                            d: elem.d,
+                        });
+                    }
                 }
-            }
-            'S' => {
-                if elem.c == 'A' {
-                    found += 1;
+                'S' => {
+                    if elem.c == 'A' {
+                        found += 1;
+                    }
                 }
-            }
-            _ => continue,
-        };
+                _ => continue,
+            };
+        }
+
+        Ok(found.into())
     }
 
-    println!("{:?}", found);
+    fn part_b(&self, grid: &Self::Input) -> Result<Answer> {
+        let mut found_b = 0;
 
-    let mut found_b = 0;
+        for (row, col) in grid.find_all(&'A') {
+            let mut diagonals = 0;
+            // The arms of the X lie 45 degrees either side of North, each with its opposite
+            for rotation in [Rotation::HalfLeft, Rotation::HalfRight] {
+                let diagonal = turn(&Direction::North, rotation);
+                let items: String = [opposite_direction(&diagonal), diagonal]
+                    .iter()
+                    .filter_map(|d| grid.get(move_direction(&(row, col), d)))
+                    .collect();
 
-    while let Some(a) = to_check_b.pop() {
-        let mut diagonals = 0;
-        // Down Right
-        let both_offsets = vec![vec![(-1, -1), (1, 1)], vec![(-1, 1), (1, -1)]];
-        for offsets in both_offsets {
-            let mut items: String = "".to_string();
-            for offset in offsets {
-                let new_r = a.row + offset.0;
-                let new_c = a.col + offset.1;
-                if is_in_bounds(rows, cols, new_r, new_c) {
-                    items.push(lines[new_r as usize][new_c as usize]);
+                if items == "MS" || items == "SM" {
+                    diagonals += 1;
                 }
             }
-
-            if items == "MS" || items == "SM" {
-                diagonals += 1;
+            if diagonals == 2 {
+                found_b += 1;
             }
         }
-        if diagonals == 2 {
-            found_b += 1;
-        }
-    }
 
-    println!("{:?}", found_b);
+        Ok(found_b.into())
+    }
 }
 
-pub fn day3() {
-    let content = fs::read_to_string("./inputs/day3.txt").expect("Couldn't read input");
-
+/// Sum of every `mul(a,b)` in the memory. When `conditional` is set the `do()` and `don't()`
+/// instructions turn the multiplications on and off.
+fn mul_total(content: &str, conditional: bool) -> i32 {
     let parent =
         Regex::new(r#"(?<do>do\(\))|(?<mul>mul\([0-9]+,[0-9]+\))|(?<dont>don't\(\))"#).unwrap();
     let matcher = Regex::new(r#"mul\(([0-9]+),([0-9]+)\)"#).unwrap();
@@ -2754,24 +1963,42 @@ pub fn day3() {
     let mut on = true;
 
     for line in content.lines() {
-        let _ = parent.captures_iter(line).for_each(|caps| {
-            if let Some(_) = caps.name("do") {
+        parent.captures_iter(line).for_each(|caps| {
+            if caps.name("do").is_some() {
                 on = true;
-            } else if let Some(_) = caps.name("dont") {
-                on = false
-            } else if let Some(x) = caps.name("mul") {
-                if on {
-                    let cap = matcher.captures(x.as_str()).unwrap();
-                    let (_, [a, b]) = cap.extract();
-                    let ai: i32 = a.parse().unwrap();
-                    let bi: i32 = b.parse().unwrap();
-                    total += ai * bi;
-                }
+            } else if caps.name("dont").is_some() {
+                on = !conditional;
+            } else if let Some(x) = caps.name("mul")
+                && on
+            {
+                let cap = matcher.captures(x.as_str()).unwrap();
+                let (_, [a, b]) = cap.extract();
+                let ai: i32 = a.parse().unwrap();
+                let bi: i32 = b.parse().unwrap();
+                total += ai * bi;
             }
         });
     }
 
-    println!("{:?}", total);
+    total
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    type Input = String;
+
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        Ok(content.to_string())
+    }
+
+    fn part_a(&self, content: &Self::Input) -> Result<Answer> {
+        Ok(mul_total(content, false).into())
+    }
+
+    fn part_b(&self, content: &Self::Input) -> Result<Answer> {
+        Ok(mul_total(content, true).into())
+    }
 }
 
 #[derive(Clone)]
@@ -2781,7 +2008,7 @@ enum LevelState {
     Decreasing,
 }
 
-enum CheckState {
+pub enum CheckState {
     Match,
     FailAt(usize),
 }
@@ -2805,96 +2032,135 @@ pub fn check(items: Vec<i32>) -> CheckState {
                 }
             }
             LevelState::Increasing => {
-                if let Some(x) = previous {
-                    if *val <= x {
-                        return CheckState::FailAt(i);
-                    }
+                if let Some(x) = previous
+                    && *val <= x
+                {
+                    return CheckState::FailAt(i);
                 }
             }
             LevelState::Decreasing => {
-                if let Some(x) = previous {
-                    if *val >= x {
-                        return CheckState::FailAt(i);
-                    }
+                if let Some(x) = previous
+                    && *val >= x
+                {
+                    return CheckState::FailAt(i);
                 }
             }
         }
 
-        if let Some(x) = previous {
-            if (*val - x).abs() > 3 {
-                return CheckState::FailAt(i);
-            }
+        if let Some(x) = previous
+            && (*val - x).abs() > 3
+        {
+            return CheckState::FailAt(i);
         }
 
         previous = Some(*val);
         state = new_state.clone();
     }
 
-    return CheckState::Match;
+    CheckState::Match
 }
 
-pub fn day2() {
-    let content = fs::read_to_string("./inputs/day2.txt").expect("Couldn't read input");
+pub struct Day2;
 
-    let mut safe = 0;
+impl Solution for Day2 {
+    type Input = Vec<Vec<i32>>;
 
-    for line in content.lines() {
-        let items: Vec<i32> = line.split(" ").map(|x| x.parse().unwrap()).collect();
-        match check(items.clone()) {
-            CheckState::Match => {
-                safe += 1;
-            }
-            CheckState::FailAt(i) => {
-                for a in 0..items.len() {
-                    let mut rest = items.clone();
-                    rest.remove(a);
-                    if let CheckState::Match = check(rest) {
-                        safe += 1;
-                        break;
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        let mut reports: Vec<Vec<i32>> = Vec::new();
+
+        for line in content.lines() {
+            let items: Vec<i32> = line
+                .split(" ")
+                .map(|x| x.parse())
+                .collect::<Result<_, _>>()?;
+            reports.push(items);
+        }
+
+        Ok(reports)
+    }
+
+    fn part_a(&self, reports: &Self::Input) -> Result<Answer> {
+        let safe = reports
+            .iter()
+            .filter(|items| matches!(check(items.to_vec()), CheckState::Match))
+            .count();
+
+        Ok(safe.into())
+    }
+
+    fn part_b(&self, reports: &Self::Input) -> Result<Answer> {
+        let mut safe = 0;
+
+        for items in reports {
+            match check(items.clone()) {
+                CheckState::Match => {
+                    safe += 1;
+                }
+                CheckState::FailAt(i) => {
+                    debug!(?items, at = i, "Trying Dampener");
+                    for a in 0..items.len() {
+                        let mut rest = items.clone();
+                        rest.remove(a);
+                        if let CheckState::Match = check(rest) {
+                            safe += 1;
+                            break;
+                        }
                     }
                 }
             }
         }
-    }
 
-    println!("{}", safe);
+        Ok(safe.into())
+    }
 }
 
-pub fn day1() {
-    let content = fs::read_to_string("./inputs/day1.txt").expect("Couldn't read input");
+pub struct Day1;
 
-    let mut left: Vec<i32> = Vec::new();
-    let mut right: Vec<i32> = Vec::new();
-    let mut right_map: HashMap<i32, i32> = HashMap::new();
+impl Solution for Day1 {
+    type Input = (Vec<i32>, Vec<i32>);
 
-    for line in content.lines() {
-        let (l, r) = line.split_once("   ").unwrap();
-        left.push(l.parse().unwrap());
-        right.push(r.parse().unwrap());
-        right_map
-            .entry(r.parse().unwrap())
-            .and_modify(|x| *x += 1)
-            .or_insert(1);
+    fn parse(&self, content: &str) -> Result<Self::Input> {
+        let mut left: Vec<i32> = Vec::new();
+        let mut right: Vec<i32> = Vec::new();
+
+        for line in content.lines() {
+            let (l, r) = line.split_once("   ").context("Bad location line")?;
+            left.push(l.parse()?);
+            right.push(r.parse()?);
+        }
+
+        Ok((left, right))
     }
 
-    let mut total = 0;
+    fn part_a(&self, (left, right): &Self::Input) -> Result<Answer> {
+        let mut total = 0;
 
-    left.sort();
-    right.sort();
+        let mut left = left.clone();
+        let mut right = right.clone();
+        left.sort();
+        right.sort();
+
+        for (a, b) in zip(left, right) {
+            total += (b - a).abs();
+        }
 
-    for (a, b) in zip(left.clone(), right) {
-        total += (b - a).abs();
+        Ok(total.into())
     }
 
-    println!("{:?}", total);
+    fn part_b(&self, (left, right): &Self::Input) -> Result<Answer> {
+        let mut right_map: HashMap<i32, i32> = HashMap::new();
+        for r in right {
+            right_map.entry(*r).and_modify(|x| *x += 1).or_insert(1);
+        }
 
-    let mut total_two = 0;
+        let mut total_two = 0;
 
-    for v in left.clone() {
-        if let Some(x) = right_map.get(&v) {
-            total_two += x * v;
+        for v in left {
+            if let Some(x) = right_map.get(v) {
+                total_two += x * v;
+            }
         }
-    }
 
-    println!("{:?}", total_two);
+        Ok(total_two.into())
+    }
 }
@@ -0,0 +1,193 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{Context, Result, bail};
+use reqwest::{blocking::Client, header};
+use tracing::{debug, info, instrument};
+
+/// Where the puzzle website lives unless `AOC_BASE_URL` says otherwise.
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com/2024";
+
+/// Directory the inputs are cached in.
+pub const INPUT_DIR: &str = "./inputs";
+
+pub const USER_AGENT: &str = concat!("advent24/", env!("CARGO_PKG_VERSION"));
+
+/// Base URL and session token for talking to the puzzle website, read from `AOC_BASE_URL` and
+/// `AOC_SESSION`.
+#[derive(Debug, Clone)]
+pub struct Site {
+    pub base_url: String,
+    pub session: Option<String>,
+}
+
+impl Site {
+    pub fn from_env() -> Site {
+        Site {
+            base_url: env::var("AOC_BASE_URL").unwrap_or(DEFAULT_BASE_URL.to_string()),
+            session: env::var("AOC_SESSION").ok().filter(|s| !s.is_empty()),
+        }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}/{}", self.base_url.trim_end_matches('/'), path)
+    }
+
+    /// The session cookie, failing with `reason` when no token is set.
+    pub fn cookie(&self, reason: &str) -> Result<String> {
+        match &self.session {
+            Some(session) => Ok(format!("session={}", session.trim())),
+            None => bail!("AOC_SESSION is not set, it is needed to {}", reason),
+        }
+    }
+
+    pub fn client(&self) -> Result<Client> {
+        Client::builder()
+            .user_agent(USER_AGENT)
+            .timeout(Duration::from_secs(30))
+            .build()
+            .context("Couldn't build HTTP client")
+    }
+}
+
+/// Resolves `dayN.txt` in a directory, downloading and caching it on first use. A cached input
+/// is never fetched again.
+pub struct InputCache {
+    dir: PathBuf,
+    site: Site,
+}
+
+impl InputCache {
+    pub fn new(dir: impl Into<PathBuf>, site: Site) -> InputCache {
+        InputCache {
+            dir: dir.into(),
+            site,
+        }
+    }
+
+    pub fn path(&self, day: u32) -> PathBuf {
+        self.dir.join(format!("day{}.txt", day))
+    }
+
+    /// Path of the input for `day`, downloading it first if it isn't cached yet.
+    pub fn resolve(&self, day: u32) -> Result<PathBuf> {
+        let path = self.path(day);
+        if path.exists() {
+            debug!(path = ?path, "Using cached input");
+            return Ok(path);
+        }
+
+        let content = self.fetch(day)?;
+        write_atomic(&path, &content)?;
+        info!(path = ?path, "Cached input");
+
+        Ok(path)
+    }
+
+    pub fn load(&self, day: u32) -> Result<String> {
+        let path = self.resolve(day)?;
+        fs::read_to_string(&path).with_context(|| format!("Couldn't read input {}", path.display()))
+    }
+
+    #[instrument(skip(self))]
+    fn fetch(&self, day: u32) -> Result<String> {
+        let cookie = self
+            .site
+            .cookie(&format!("download {}", self.path(day).display()))?;
+        let url = self.site.url(&format!("day/{}/input", day));
+        info!(url, "Downloading input");
+
+        let response = self
+            .site
+            .client()?
+            .get(&url)
+            .header(header::COOKIE, cookie)
+            .send()
+            .with_context(|| format!("Couldn't download {}", url))?
+            .error_for_status()
+            .with_context(|| format!("Couldn't download {}", url))?;
+
+        response
+            .text()
+            .with_context(|| format!("Couldn't read response from {}", url))
+    }
+}
+
+/// Write via a temporary file so an interrupted download never leaves a partial input behind.
+fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Couldn't create {}", dir.display()))?;
+    }
+    let tmp = path.with_extension("txt.part");
+    fs::write(&tmp, content).with_context(|| format!("Couldn't write {}", tmp.display()))?;
+    fs::rename(&tmp, path).with_context(|| format!("Couldn't write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process;
+
+    use super::*;
+    use crate::mock_http::MockServer;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("advent24-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn downloads_once_and_caches() {
+        let server = MockServer::start(vec![(200, "1 2\n3 4\n")]);
+        let dir = temp_dir("cache");
+        let site = Site {
+            base_url: format!("{}/2024/", server.url),
+            session: Some("secret".to_string()),
+        };
+        let cache = InputCache::new(&dir, site);
+
+        assert_eq!(cache.load(1).unwrap(), "1 2\n3 4\n");
+        // Served from disk, the server only has one response to give.
+        assert_eq!(cache.load(1).unwrap(), "1 2\n3 4\n");
+
+        let requests = server.finish();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(requests[0].path, "/2024/day/1/input");
+        assert_eq!(requests[0].header("cookie"), Some("session=secret"));
+        assert!(dir.join("day1.txt").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn failed_download_is_not_cached() {
+        let server = MockServer::start(vec![(404, "Not yet")]);
+        let dir = temp_dir("missing");
+        let site = Site {
+            base_url: server.url.clone(),
+            session: Some("secret".to_string()),
+        };
+        let cache = InputCache::new(&dir, site);
+
+        assert!(cache.load(2).is_err());
+        server.finish();
+        assert!(!cache.path(2).exists());
+
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn needs_a_session_to_download() {
+        let dir = temp_dir("session");
+        let site = Site {
+            base_url: "http://127.0.0.1:1".to_string(),
+            session: None,
+        };
+        let err = InputCache::new(&dir, site).load(3).unwrap_err();
+        assert!(format!("{:#}", err).contains("AOC_SESSION"));
+    }
+}
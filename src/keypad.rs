@@ -0,0 +1,347 @@
+//! Keypads of day 21 and the cost of typing on one through a chain of robots, each working the
+//! keypad below from a directional keypad of its own.
+
+use std::collections::{HashMap, VecDeque};
+
+use anyhow::{Context, Result, bail};
+use num_bigint::BigUint;
+
+use crate::util::{Direction, Grid, Position, move_direction};
+
+/// The door's keypad. `#` marks the gap no robot arm may pass over.
+pub const NUMERIC: &str = "789\n456\n123\n#0A";
+
+/// The keypad every robot is driven from.
+pub const DIRECTIONAL: &str = "#^A\n<v>";
+
+pub struct Keypad {
+    keys: HashMap<char, Position>,
+}
+
+impl Keypad {
+    /// Build a keypad from an ASCII layout, one character per key and `#` for each gap. Every
+    /// keypad needs an `A` key, which is where robots start and what they press to activate.
+    pub fn parse(layout: &str) -> Result<Keypad> {
+        let grid = Grid::parse_chars(layout)?;
+        let mut keys = HashMap::new();
+        for (pos, key) in grid.iter() {
+            if *key != '#' && keys.insert(*key, pos).is_some() {
+                bail!("Key {} appears twice", key);
+            }
+        }
+        if !keys.contains_key(&'A') {
+            bail!("Keypad has no A key");
+        }
+        Ok(Keypad { keys })
+    }
+
+    pub fn numeric() -> Keypad {
+        Keypad::parse(NUMERIC).expect("Numeric keypad layout is valid")
+    }
+
+    pub fn directional() -> Keypad {
+        Keypad::parse(DIRECTIONAL).expect("Directional keypad layout is valid")
+    }
+
+    pub fn position(&self, key: char) -> Result<Position> {
+        self.keys
+            .get(&key)
+            .copied()
+            .with_context(|| format!("There is no {} key", key))
+    }
+
+    /// The key at a position, or `None` over the gap or off the keypad.
+    pub fn key_at(&self, pos: Position) -> Option<char> {
+        self.keys
+            .iter()
+            .find(|(_, p)| **p == pos)
+            .map(|(key, _)| *key)
+    }
+
+    /// Every shortest way to move from one key to another without passing over a gap, in the
+    /// order North, East, South, West is tried at each step. Routes can't all be straight lines
+    /// or single turns, as a gap between two keys in a row has to be gone round.
+    pub fn routes(&self, from: char, to: char) -> Result<Vec<Vec<Direction>>> {
+        let (start, end) = (self.position(from)?, self.position(to)?);
+
+        // Steps from each key to the end, walking only over keys
+        let mut distance: HashMap<Position, usize> = HashMap::from([(end, 0)]);
+        let mut queue = VecDeque::from([end]);
+        while let Some(pos) = queue.pop_front() {
+            for d in Direction::CARDINAL {
+                let next = move_direction(&pos, &d);
+                if self.key_at(next).is_some() && !distance.contains_key(&next) {
+                    distance.insert(next, distance[&pos] + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        let mut routes = Vec::new();
+        if distance.contains_key(&start) {
+            self.extend_routes(start, &distance, &mut Vec::new(), &mut routes);
+        }
+        Ok(routes)
+    }
+
+    /// Add every route from `pos` that gets one step closer to the end each move.
+    fn extend_routes(
+        &self,
+        pos: Position,
+        distance: &HashMap<Position, usize>,
+        route: &mut Vec<Direction>,
+        routes: &mut Vec<Vec<Direction>>,
+    ) {
+        if distance[&pos] == 0 {
+            routes.push(route.clone());
+            return;
+        }
+        for d in Direction::CARDINAL {
+            let next = move_direction(&pos, &d);
+            if distance.get(&next) == Some(&(distance[&pos] - 1)) {
+                route.push(d);
+                self.extend_routes(next, distance, route, routes);
+                route.pop();
+            }
+        }
+    }
+}
+
+/// A stack of keypads, each worked by a robot arm driven from the next, with the person driving
+/// the last arm from a directional keypad of their own.
+pub struct KeypadChain {
+    pads: Vec<Keypad>,
+    /// Presses needed to move layer's robot between two keys and press the second.
+    memo: HashMap<(usize, char, char), BigUint>,
+}
+
+impl KeypadChain {
+    pub fn new(pads: Vec<Keypad>) -> KeypadChain {
+        KeypadChain {
+            pads,
+            memo: HashMap::new(),
+        }
+    }
+
+    /// The door's numeric keypad with `robots` directional keypads between it and the person.
+    pub fn door(robots: usize) -> KeypadChain {
+        let mut pads = vec![Keypad::numeric()];
+        pads.extend((0..robots).map(|_| Keypad::directional()));
+        KeypadChain::new(pads)
+    }
+
+    /// Fewest presses the person has to make for `code` to be typed on the first keypad.
+    pub fn presses(&mut self, code: &str) -> Result<BigUint> {
+        self.sequence_cost(0, code.chars())
+    }
+
+    /// Presses to type `keys` on `layer`, with its robot starting on `A`.
+    fn sequence_cost(&mut self, layer: usize, keys: impl Iterator<Item = char>) -> Result<BigUint> {
+        let mut total = BigUint::ZERO;
+        let mut from = 'A';
+        for to in keys {
+            total += self.cost(layer, from, to)?;
+            from = to;
+        }
+        Ok(total)
+    }
+
+    /// Cheapest route from one key to another, as the keys to press on the layer above.
+    fn best_route(&mut self, layer: usize, from: char, to: char) -> Result<Vec<char>> {
+        let mut best: Option<(BigUint, Vec<char>)> = None;
+        for route in self.pads[layer].routes(from, to)? {
+            let keys: Vec<char> = route
+                .iter()
+                .map(|d| d.arrow().expect("Routes only move in cardinal directions"))
+                .chain(['A'])
+                .collect();
+            let cost = self.sequence_cost(layer + 1, keys.iter().copied())?;
+            if best.as_ref().is_none_or(|(b, _)| cost < *b) {
+                best = Some((cost, keys));
+            }
+        }
+        best.map(|(_, keys)| keys)
+            .with_context(|| format!("No way from {} to {}", from, to))
+    }
+
+    fn expand(&mut self, layer: usize, keys: &[char], presses: &mut String) -> Result<()> {
+        if layer == self.pads.len() {
+            presses.extend(keys);
+            return Ok(());
+        }
+        let mut from = 'A';
+        for to in keys {
+            let route = self.best_route(layer, from, *to)?;
+            self.expand(layer + 1, &route, presses)?;
+            from = *to;
+        }
+        Ok(())
+    }
+
+    /// One of the shortest sequences of presses the person can make to type `code`. These grow
+    /// exponentially with the number of robots, so only sequences up to `limit` long are written.
+    pub fn sequence(&mut self, code: &str, limit: usize) -> Result<String> {
+        let length = self.presses(code)?;
+        if length > BigUint::from(limit) {
+            bail!(
+                "The sequence for {} is {} presses long, more than the limit of {}",
+                code,
+                length,
+                limit
+            );
+        }
+
+        let keys: Vec<char> = code.chars().collect();
+        let mut presses = String::new();
+        self.expand(0, &keys, &mut presses)?;
+        Ok(presses)
+    }
+
+    /// Press `presses` on the directional keypad driving the last robot and return what gets
+    /// typed on the first keypad. Fails as soon as any robot arm would move over a gap or off its
+    /// keypad.
+    pub fn replay(&self, presses: &str) -> Result<String> {
+        let mut arms: Vec<Position> = self
+            .pads
+            .iter()
+            .map(|pad| pad.position('A'))
+            .collect::<Result<_>>()?;
+        let mut typed = String::new();
+
+        for (i, press) in presses.chars().enumerate() {
+            // Arrows move the arm over a keypad, `A` presses the key under it, which in turn is
+            // an arrow or `A` for the arm over the keypad below
+            let mut key = press;
+            let mut layer = self.pads.len();
+            while layer > 0 {
+                layer -= 1;
+                let pad = &self.pads[layer];
+                if key == 'A' {
+                    key = pad.key_at(arms[layer]).expect("Arms only rest on keys");
+                    if layer == 0 {
+                        typed.push(key);
+                    }
+                    continue;
+                }
+
+                let direction = Direction::from_arrow(key)
+                    .with_context(|| format!("Press {} of {:?} isn't a direction", i, key))?;
+                let next = move_direction(&arms[layer], &direction);
+                if pad.key_at(next).is_none() {
+                    bail!(
+                        "Press {} moves the arm on keypad {} off its keys to {:?}",
+                        i,
+                        layer,
+                        next
+                    );
+                }
+                arms[layer] = next;
+                break;
+            }
+        }
+
+        Ok(typed)
+    }
+
+    /// Every layer above has to be back on `A` for this layer's key to be pressed, so the cost of
+    /// one move only depends on the layer and the two keys.
+    fn cost(&mut self, layer: usize, from: char, to: char) -> Result<BigUint> {
+        if layer == self.pads.len() {
+            return Ok(BigUint::from(1u8));
+        }
+        if let Some(cost) = self.memo.get(&(layer, from, to)) {
+            return Ok(cost.clone());
+        }
+
+        let route = self.best_route(layer, from, to)?;
+        let best = self.sequence_cost(layer + 1, route.into_iter())?;
+        self.memo.insert((layer, from, to), best.clone());
+        Ok(best)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes_avoid_the_gap() {
+        let pad = Keypad::numeric();
+        let arrows = |from, to| -> Vec<String> {
+            pad.routes(from, to)
+                .unwrap()
+                .iter()
+                .map(|r| r.iter().filter_map(Direction::arrow).collect())
+                .collect()
+        };
+        assert_eq!(arrows('A', '1'), ["^<<", "<^<"]);
+        assert_eq!(arrows('7', '0'), [">vvv", "v>vv", "vv>v"]);
+        assert_eq!(arrows('2', '9'), ["^^>", "^>^", ">^^"]);
+        assert_eq!(arrows('5', '5'), [""]);
+
+        // A gap between two keys in the same row has to be gone round
+        let split = Keypad::parse("1#2\n34A").unwrap();
+        let routes = split.routes('1', '2').unwrap();
+        assert_eq!(routes.len(), 1);
+        assert_eq!(
+            routes[0]
+                .iter()
+                .filter_map(Direction::arrow)
+                .collect::<String>(),
+            "v>>^"
+        );
+        let mut chain = KeypadChain::new(vec![split, Keypad::directional()]);
+        let sequence = chain.sequence("12A", 100).unwrap();
+        assert_eq!(BigUint::from(sequence.len()), chain.presses("12A").unwrap());
+        assert_eq!(chain.replay(&sequence).unwrap(), "12A");
+
+        assert!(
+            Keypad::parse("1#\n#A")
+                .unwrap()
+                .routes('1', 'A')
+                .unwrap()
+                .is_empty()
+        );
+        assert!(Keypad::parse("12\n34").is_err());
+        assert!(Keypad::parse("11\n3A").is_err());
+    }
+
+    #[test]
+    fn counts_presses_through_robots() {
+        let presses = |robots, code| KeypadChain::door(robots).presses(code).unwrap();
+        assert_eq!(presses(0, "029A"), BigUint::from(12u8));
+        assert_eq!(presses(1, "029A"), BigUint::from(28u8));
+        assert_eq!(presses(2, "029A"), BigUint::from(68u8));
+        assert_eq!(presses(2, "379A"), BigUint::from(64u8));
+        assert_eq!(
+            presses(100, "029A").to_string(),
+            "39623698645360671878530501931953158941494"
+        );
+    }
+
+    #[test]
+    fn replays_optimal_sequences() {
+        for robots in 0..=3 {
+            let mut chain = KeypadChain::door(robots);
+            for code in ["029A", "980A", "179A", "456A", "379A"] {
+                let sequence = chain.sequence(code, 10_000).unwrap();
+                assert_eq!(BigUint::from(sequence.len()), chain.presses(code).unwrap());
+                assert_eq!(chain.replay(&sequence).unwrap(), code);
+            }
+        }
+
+        let chain = KeypadChain::door(2);
+        assert_eq!(
+            chain
+                .replay("<vA<AA>>^AvAA<^A>A<v<A>>^AvA^A<vA>^A<v<A>^A>AAvA^A<v<A>A>^AAAvA<^A>A")
+                .unwrap(),
+            "029A"
+        );
+        let err = KeypadChain::door(0).replay("<<").unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "Press 1 moves the arm on keypad 0 off its keys to (3, 0)"
+        );
+        assert!(KeypadChain::door(25).sequence("029A", 10_000).is_err());
+    }
+}
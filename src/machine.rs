@@ -0,0 +1,374 @@
+//! The three-bit computer of day 17: three registers, an instruction pointer and a program of
+//! 3-bit opcode/operand pairs.
+
+use std::fmt;
+
+use anyhow::{Context, Result, bail};
+use itertools::Itertools;
+use tracing::{debug, warn};
+
+/// Steps a program may take before `run` gives up on it.
+pub const STEP_LIMIT: usize = 1_000_000;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Machine {
+    pub a: u64,
+    pub b: u64,
+    pub c: u64,
+    pub ip: usize,
+    pub program: Vec<u8>,
+    pub output: Vec<u8>,
+    /// Instructions executed so far.
+    pub steps: usize,
+}
+
+/// `a >> n`, without overflowing on shifts of 64 or more.
+fn shift(a: u64, n: u64) -> u64 {
+    if n >= 64 { 0 } else { a >> n }
+}
+
+impl Machine {
+    /// Parse the `Register A: ...` lines followed by `Program: ...`.
+    pub fn parse(content: &str) -> Result<Machine> {
+        let mut lines = content.lines().filter(|l| !l.is_empty());
+        let mut field = |name: &str| -> Result<&str> {
+            let line = lines.next().with_context(|| format!("Missing {}", name))?;
+            line.strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix(": "))
+                .with_context(|| format!("Expected {} in {:?}", name, line))
+        };
+
+        let a = field("Register A")?.parse()?;
+        let b = field("Register B")?.parse()?;
+        let c = field("Register C")?.parse()?;
+        let program = field("Program")?
+            .split(',')
+            .map(|x| x.trim().parse())
+            .collect::<Result<_, _>>()
+            .context("Bad program")?;
+
+        Ok(Machine::new(a, b, c, program))
+    }
+
+    pub fn new(a: u64, b: u64, c: u64, program: Vec<u8>) -> Machine {
+        Machine {
+            a,
+            b,
+            c,
+            ip: 0,
+            program,
+            output: Vec::new(),
+            steps: 0,
+        }
+    }
+
+    /// A fresh copy of the machine with register A replaced.
+    pub fn with_a(&self, a: u64) -> Machine {
+        Machine::new(a, self.b, self.c, self.program.clone())
+    }
+
+    /// The machine halts when the instruction pointer runs off the end of the program.
+    pub fn halted(&self) -> bool {
+        self.ip + 1 >= self.program.len()
+    }
+
+    fn combo(&self, operand: u8) -> Result<u64> {
+        match operand {
+            0..=3 => Ok(operand as u64),
+            4 => Ok(self.a),
+            5 => Ok(self.b),
+            6 => Ok(self.c),
+            7 => bail!("Combo operand 7 is reserved, at IP {}", self.ip),
+            _ => bail!("Invalid operand {} at IP {}", operand, self.ip),
+        }
+    }
+
+    /// Execute the instruction at the instruction pointer.
+    pub fn step(&mut self) -> Result<()> {
+        if self.halted() {
+            bail!("Machine has halted");
+        }
+        let opcode = self.program[self.ip];
+        let operand = self.program[self.ip + 1];
+
+        let mut next = self.ip + 2;
+        match opcode {
+            0 => self.a = shift(self.a, self.combo(operand)?),
+            1 => self.b ^= operand as u64,
+            2 => self.b = self.combo(operand)? % 8,
+            3 => {
+                if self.a != 0 {
+                    next = operand as usize;
+                }
+            }
+            4 => self.b ^= self.c,
+            5 => self.output.push((self.combo(operand)? % 8) as u8),
+            6 => self.b = shift(self.a, self.combo(operand)?),
+            7 => self.c = shift(self.a, self.combo(operand)?),
+            _ => bail!("Invalid opcode {} at IP {}", opcode, self.ip),
+        }
+
+        self.ip = next;
+        self.steps += 1;
+        Ok(())
+    }
+
+    /// Step until the machine halts or `stop` says so, failing once `limit` steps have been taken.
+    pub fn run_until(
+        &mut self,
+        limit: usize,
+        mut stop: impl FnMut(&Machine) -> bool,
+    ) -> Result<()> {
+        while !self.halted() && !stop(self) {
+            if self.steps >= limit {
+                bail!("Program still running after {} steps", limit);
+            }
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    pub fn run(&mut self, limit: usize) -> Result<()> {
+        self.run_until(limit, |_| false)
+    }
+
+    pub fn output_string(&self) -> String {
+        self.output.iter().join(",")
+    }
+
+    /// The instruction at the instruction pointer, if the machine hasn't halted.
+    pub fn current(&self) -> Option<String> {
+        (!self.halted()).then(|| mnemonic(self.program[self.ip], self.program[self.ip + 1]))
+    }
+}
+
+impl fmt::Display for Machine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ip={:<3} a={} b={} c={} out=[{}]",
+            self.ip,
+            self.a,
+            self.b,
+            self.c,
+            self.output_string()
+        )
+    }
+}
+
+fn combo_name(operand: u8) -> String {
+    match operand {
+        0..=3 => operand.to_string(),
+        4 => "A".to_string(),
+        5 => "B".to_string(),
+        6 => "C".to_string(),
+        _ => format!("?{}", operand),
+    }
+}
+
+/// The instruction as a mnemonic followed by what it computes, like `adv A>>3` or `out B%8`.
+pub fn mnemonic(opcode: u8, operand: u8) -> String {
+    let combo = combo_name(operand);
+    match opcode {
+        0 => format!("adv A>>{}", combo),
+        1 => format!("bxl B^{}", operand),
+        2 => format!("bst {}%8", combo),
+        3 => format!("jnz {}", operand),
+        4 => "bxc B^C".to_string(),
+        5 => format!("out {}%8", combo),
+        6 => format!("bdv A>>{}", combo),
+        7 => format!("cdv A>>{}", combo),
+        _ => format!("??? {},{}", opcode, operand),
+    }
+}
+
+/// One line per instruction, each prefixed by its address.
+pub fn disassemble(program: &[u8]) -> Vec<String> {
+    program
+        .chunks(2)
+        .enumerate()
+        .map(|(i, pair)| match pair {
+            [opcode, operand] => format!("{:>3}: {}", i * 2, mnemonic(*opcode, *operand)),
+            _ => format!("{:>3}: {} (no operand)", i * 2, pair[0]),
+        })
+        .collect()
+}
+
+/// Largest register A tried when the program can't be searched digit by digit.
+pub const BRUTE_FORCE_LIMIT: u64 = 1 << 24;
+
+/// Why the program doesn't have the usual shape: a single loop that outputs one value and shifts
+/// A right by 3 each time round, jumping back to the start while A is non-zero.
+fn loop_mismatch(program: &[u8]) -> Option<String> {
+    let instructions: Vec<(u8, u8)> = program.chunks_exact(2).map(|c| (c[0], c[1])).collect();
+    if instructions.last() != Some(&(3, 0)) {
+        return Some("it doesn't end by jumping back to the start".to_string());
+    }
+    let count = |f: &dyn Fn(&(u8, u8)) -> bool| instructions.iter().filter(|i| f(i)).count();
+    if count(&|i| i.0 == 3) != 1 {
+        return Some("it has more than one jump".to_string());
+    }
+    if count(&|i| *i == (0, 3)) != 1 || count(&|i| i.0 == 0) != 1 {
+        return Some("it doesn't shift A by exactly 3 per loop".to_string());
+    }
+    if count(&|i| i.0 == 5) != 1 {
+        return Some("it doesn't output exactly once per loop".to_string());
+    }
+    None
+}
+
+/// Run a candidate until it halts or `stop` says so, returning false rather than failing if it is
+/// still going after `STEP_LIMIT` steps, as some A can make a program loop forever.
+fn halts(run: &mut Machine, stop: impl FnMut(&Machine) -> bool) -> Result<bool> {
+    match run.run_until(STEP_LIMIT, stop) {
+        Ok(()) => Ok(true),
+        Err(_) if run.steps >= STEP_LIMIT => {
+            debug!(steps = run.steps, "Skipping an A that never halts");
+            Ok(false)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Build A three bits at a time, from the last output back to the first. The final loop only
+/// sees the top octal digit of A, the one before it the top two and so on, so every A giving
+/// the last `n` values of the program extends one giving the last `n - 1`.
+fn reverse_search(machine: &Machine) -> Result<Option<u64>> {
+    let program = &machine.program;
+    let mut candidates: Vec<u64> = vec![0];
+    for start in (0..program.len()).rev() {
+        let mut next = Vec::new();
+        for high in &candidates {
+            for digit in 0..8 {
+                let a = (high << 3) | digit;
+                let mut run = machine.with_a(a);
+                if halts(&mut run, |_| false)? && run.output == program[start..] {
+                    next.push(a);
+                }
+            }
+        }
+        debug!(start, candidates = next.len(), "Matched output tail");
+        candidates = next;
+    }
+    Ok(candidates.into_iter().filter(|a| *a > 0).min())
+}
+
+/// Try every A up to `limit`, dropping each run as soon as its output stops matching.
+fn brute_force(machine: &Machine, limit: u64) -> Result<Option<u64>> {
+    for a in 1..=limit {
+        let mut run = machine.with_a(a);
+        if !halts(&mut run, |m| !m.program.starts_with(&m.output))? {
+            continue;
+        }
+        if run.output == run.program {
+            return Ok(Some(a));
+        }
+    }
+    Ok(None)
+}
+
+/// The lowest register A for which the program outputs itself, trying every A up to `limit`
+/// if the program doesn't have the shape needed to search it digit by digit.
+pub fn find_quine(machine: &Machine, limit: u64) -> Result<u64> {
+    let reason = match loop_mismatch(&machine.program) {
+        Some(reason) => reason,
+        None => match reverse_search(machine)? {
+            Some(a) => return Ok(a),
+            None => "searching it digit by digit found nothing".to_string(),
+        },
+    };
+
+    warn!(
+        "Can't search the program digit by digit as {}, trying A up to {}",
+        reason, limit
+    );
+    brute_force(machine, limit)?.with_context(|| {
+        format!(
+            "No A up to {} makes the program output itself, and it can't be searched digit by \
+             digit as {}",
+            limit, reason
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(a: u64, b: u64, c: u64, program: &[u8]) -> Result<Machine> {
+        let mut machine = Machine::new(a, b, c, program.to_vec());
+        machine.run(STEP_LIMIT)?;
+        Ok(machine)
+    }
+
+    #[test]
+    fn runs_small_programs() {
+        assert_eq!(run(0, 0, 9, &[2, 6]).unwrap().b, 1);
+        assert_eq!(
+            run(10, 0, 0, &[5, 0, 5, 1, 5, 4]).unwrap().output,
+            [0, 1, 2]
+        );
+
+        let machine = run(2024, 0, 0, &[0, 1, 5, 4, 3, 0]).unwrap();
+        assert_eq!(machine.output_string(), "4,2,5,6,7,7,7,7,3,1,0");
+        assert_eq!(machine.a, 0);
+
+        assert_eq!(run(0, 29, 0, &[1, 7]).unwrap().b, 26);
+        assert_eq!(run(0, 2024, 43690, &[4, 0]).unwrap().b, 44354);
+
+        let machine = Machine::parse(
+            "Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,5,4,3,0\n",
+        )
+        .unwrap();
+        assert_eq!((machine.a, machine.program.len()), (729, 6));
+    }
+
+    #[test]
+    fn reports_bad_programs() {
+        let err = run(0, 0, 0, &[5, 7]).unwrap_err().to_string();
+        assert_eq!(err, "Combo operand 7 is reserved, at IP 0");
+
+        let err = run(0, 0, 0, &[1, 1, 8, 0]).unwrap_err().to_string();
+        assert_eq!(err, "Invalid opcode 8 at IP 2");
+
+        let err = run(1, 0, 0, &[3, 0]).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            format!("Program still running after {} steps", STEP_LIMIT)
+        );
+    }
+
+    #[test]
+    fn finds_quines() {
+        let quine = Machine::new(2024, 0, 0, vec![0, 3, 5, 4, 3, 0]);
+        assert_eq!(find_quine(&quine, 0).unwrap(), 117440);
+
+        // Shifting by 1 doesn't fit the digit search, and no A makes it a quine
+        let shift_one = Machine::new(0, 0, 0, vec![0, 1, 5, 4, 3, 0]);
+        let err = find_quine(&shift_one, 1000).unwrap_err().to_string();
+        assert!(err.contains("doesn't shift A by exactly 3"), "{}", err);
+
+        assert_eq!(brute_force(&quine, 200_000).unwrap(), Some(117440));
+        // Every A loops forever, which rules them out rather than failing the search
+        let forever = Machine::new(0, 0, 0, vec![3, 0]);
+        assert_eq!(brute_force(&forever, 2).unwrap(), None);
+    }
+
+    #[test]
+    fn disassembles() {
+        assert_eq!(
+            disassemble(&[2, 4, 1, 3, 7, 5, 0, 3, 4, 1, 5, 5, 3, 0, 5, 7, 9]),
+            [
+                "  0: bst A%8",
+                "  2: bxl B^3",
+                "  4: cdv A>>B",
+                "  6: adv A>>3",
+                "  8: bxc B^C",
+                " 10: out B%8",
+                " 12: jnz 0",
+                " 14: out ?7%8",
+                " 16: 9 (no operand)",
+            ]
+        );
+    }
+}
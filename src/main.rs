@@ -1,104 +1,532 @@
-use core::panic;
 use std::{
-    collections::{HashMap, HashSet},
-    fs,
-    iter::zip,
+    collections::HashSet,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::Path,
 };
 
-use anyhow::{Context, Result};
-use itertools::{Combinations, Itertools, Permutations};
-use tracing::{Level, debug, error, event, info, instrument, warn};
-use tracing_subscriber::{EnvFilter, field::debug};
+use anyhow::{Context, Result, bail};
+use clap::{Parser, Subcommand};
+use itertools::Itertools;
+use tracing::{info, info_span, warn};
+use tracing_subscriber::EnvFilter;
 
+mod adder;
+mod animation;
+mod circuit;
+mod claw;
+mod debugger;
 mod done;
+mod inputs;
+mod keypad;
+mod machine;
+#[cfg(test)]
+mod mock_http;
+mod regression;
+mod render;
+mod schematic;
+mod solution;
+mod submit;
+mod swarm;
+mod timing;
 mod util;
+mod warehouse;
 
-#[instrument]
-pub fn dayxx(part_b: bool) -> Result<()> {
-    //let content = fs::read_to_string(filename).context("Couldn't read input")?;
+use circuit::Circuit;
+use debugger::Debugger;
+use inputs::{INPUT_DIR, InputCache, Site};
+use keypad::KeypadChain;
+use machine::{Machine, disassemble};
+use regression::{ANSWERS_FILE, check, parse_answers, record_missing};
+use render::{DEFAULT_PALETTE, Image, ImageFormat, Palette};
+use schematic::{Format, render};
+use solution::{Answer, DAYS, PICTURE_DAYS, Part, day_params, parse_param, picture, solver};
+use submit::{HISTORY_FILE, History, Submitter, Verdict};
+use timing::{Summary, bench, time_day};
+use warehouse::{RobotPlan, Warehouse};
 
+#[derive(Parser)]
+#[command(about = "Advent of Code 2024 solutions")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the solution for a single day
+    Run {
+        /// Day to run (1-25)
+        day: u32,
+
+        /// Which part of the puzzle to run
+        #[arg(long, value_enum, default_value_t = Part::A)]
+        part: Part,
+
+        /// Input file, defaults to ./inputs/dayN.txt which is downloaded if missing
+        #[arg(long)]
+        input: Option<String>,
+
+        /// Day specific parameter as name=value, see `list`
+        #[arg(long = "param", short = 'p', value_parser = parse_param)]
+        params: Vec<(String, String)>,
+    },
+    /// Run a day and submit the answer, unless the submission history shows it can't be right
+    Submit {
+        /// Day to submit (1-25)
+        day: u32,
+
+        /// Which part of the puzzle to submit
+        #[arg(value_enum)]
+        part: Part,
+
+        /// Day specific parameter as name=value, see `list`
+        #[arg(long = "param", short = 'p', value_parser = parse_param)]
+        params: Vec<(String, String)>,
+    },
+    /// Time the parse and both parts of each day and print a summary table
+    Time {
+        /// Days to time, defaults to every day
+        days: Vec<u32>,
+    },
+    /// Repeat the parse and both parts of a day and report min/median/max times
+    Bench {
+        /// Day to bench (1-25)
+        day: u32,
+
+        /// Number of times to repeat each step
+        #[arg(long, short = 'n', default_value_t = 10)]
+        repeats: usize,
+
+        /// Input file, defaults to ./inputs/dayN.txt which is downloaded if missing
+        #[arg(long)]
+        input: Option<String>,
+
+        /// Day specific parameter as name=value, see `list`
+        #[arg(long = "param", short = 'p', value_parser = parse_param)]
+        params: Vec<(String, String)>,
+    },
+    /// Download the input for a day into ./inputs, unless it is already there
+    Fetch {
+        /// Day to fetch (1-25)
+        day: u32,
+    },
+    /// List the implemented days and the extra parameters they take
+    List,
+    /// Draw the day 24 circuit as a Graphviz DOT or Mermaid graph
+    Circuit {
+        /// Input file, defaults to ./inputs/day24.txt which is downloaded if missing
+        #[arg(long)]
+        input: Option<String>,
+
+        /// Graph language to write
+        #[arg(long, value_enum, default_value_t = Format::Dot)]
+        format: Format,
+
+        /// Highlight the wires the adder check finds swapped
+        #[arg(long)]
+        repair: bool,
+
+        /// Extra wires to highlight, as comma separated names
+        #[arg(long, value_delimiter = ',')]
+        highlight: Vec<String>,
+    },
+    /// Disassemble the day 17 program or step through it
+    Machine {
+        /// Input file, defaults to ./inputs/day17.txt which is downloaded if missing
+        #[arg(long)]
+        input: Option<String>,
+
+        /// Start with this in register A instead of the value from the input
+        #[arg(short)]
+        a: Option<u64>,
+
+        /// Print the program as mnemonics instead of running it
+        #[arg(long)]
+        disassemble: bool,
+
+        /// Print the registers and output before every step
+        #[arg(long)]
+        trace: bool,
+
+        /// Pause before the instruction at this address
+        #[arg(long = "break-ip")]
+        break_ips: Vec<usize>,
+
+        /// Pause once the output with this index has been written
+        #[arg(long = "break-output")]
+        break_outputs: Vec<usize>,
+    },
+    /// Show the day 15 warehouse partway through the robot's instructions
+    Warehouse {
+        /// Input file, defaults to ./inputs/day15.txt which is downloaded if missing
+        #[arg(long)]
+        input: Option<String>,
+
+        /// Use the double width warehouse of part B
+        #[arg(long)]
+        wide: bool,
+
+        /// Instructions to follow, defaults to all of them
+        #[arg(long)]
+        steps: Option<usize>,
+
+        /// Draw every step in the terminal
+        #[arg(long)]
+        animate: bool,
+
+        /// Write every step as a frame of an animated GIF instead
+        #[arg(long)]
+        gif: Option<String>,
+
+        /// Frames per second of the animation
+        #[arg(long, default_value_t = 10.0)]
+        fps: f64,
+
+        /// Pixels per tile in the GIF
+        #[arg(long, default_value_t = 4)]
+        scale: usize,
+    },
+    /// Print a shortest sequence of presses typing a day 21 code, checked by replaying it
+    Keypad {
+        /// Code to type on the door, like 029A
+        code: String,
+
+        /// Robots on directional keypads between you and the door
+        #[arg(long, default_value_t = 2)]
+        robots: usize,
+
+        /// Longest sequence to print
+        #[arg(long, default_value_t = 10_000)]
+        limit: usize,
+    },
+    /// Save a picture of a day's puzzle as a PPM, PNG or SVG image
+    Draw {
+        /// Day to draw: 12, 14, 16 or 18
+        day: u32,
+
+        /// Image file to write
+        output: String,
+
+        /// Input file, defaults to ./inputs/dayN.txt which is downloaded if missing
+        #[arg(long)]
+        input: Option<String>,
+
+        /// Image format, defaults to the one the output's extension names
+        #[arg(long, value_enum)]
+        format: Option<ImageFormat>,
+
+        /// Pixels per cell
+        #[arg(long, default_value_t = 4)]
+        scale: usize,
+
+        /// Comma separated #rrggbb colours: background, walls, highlights, then any others
+        #[arg(long, default_value = DEFAULT_PALETTE)]
+        palette: String,
+
+        /// Day specific parameter as name=value, see `list`
+        #[arg(long = "param", short = 'p', value_parser = parse_param)]
+        params: Vec<(String, String)>,
+    },
+    /// Check the days against the answers recorded in ./inputs/answers.txt
+    Check {
+        /// Only check this day
+        #[arg(long)]
+        day: Option<u32>,
+
+        /// Also append answers for full inputs in ./inputs that have none recorded yet
+        #[arg(long)]
+        record: bool,
+    },
+}
+
+fn load_input(day: u32, input: Option<String>) -> Result<String> {
+    match input {
+        Some(filename) => fs::read_to_string(&filename)
+            .with_context(|| format!("Couldn't read input {}", filename)),
+        None => InputCache::new(INPUT_DIR, Site::from_env()).load(day),
+    }
+}
+
+fn solve(
+    day: u32,
+    part: Part,
+    input: Option<String>,
+    params: &[(String, String)],
+) -> Result<Answer> {
+    let solver = solver(day, params)?;
+    let content = load_input(day, input)?;
+
+    let _span = info_span!("run", day, %part).entered();
+    solver.solve(&content, part)
+}
+
+fn run(day: u32, part: Part, input: Option<String>, params: &[(String, String)]) -> Result<()> {
+    let answer = solve(day, part, input, params)?;
+    println!("Day {} part {}: {}", day, part, answer);
     Ok(())
 }
 
-#[derive(Clone)]
-enum KeyLocks {
-    Key(Vec<i32>),
-    Lock(Vec<i32>),
-}
-
-#[instrument]
-pub fn day25(filename: &str, part_b: bool) -> Result<()> {
-    let content = fs::read_to_string(filename).context("Couldn't read input")?;
-
-    let kls: Vec<KeyLocks> = content
-        .split("\n\n")
-        .map(|grid| {
-            let mut values: Vec<i32> = vec![0, 0, 0, 0, 0];
-            let mut is_key = true;
-            for (r, line) in grid.lines().enumerate() {
-                for (c, v) in line.chars().enumerate() {
-                    if r == 0 && v == '#' {
-                        is_key = false;
-                    }
-
-                    if v == '#' {
-                        if is_key {
-                            values[c] = values[c].max(6 - r as i32);
-                        } else {
-                            values[c] = values[c].max(r as i32);
-                        }
-                    }
-                }
-            }
+fn submit(day: u32, part: Part, params: &[(String, String)]) -> Result<()> {
+    let answer = solve(day, part, None, params)?;
+    println!("Day {} part {}: {}", day, part, answer);
+
+    let history = History::load(Path::new(INPUT_DIR).join(HISTORY_FILE))?;
+    let verdict = Submitter::new(Site::from_env(), history).submit(day, part, &answer)?;
+    println!("{}", verdict);
 
-            if is_key {
-                return KeyLocks::Key(values);
-            } else {
-                return KeyLocks::Lock(values);
+    if !matches!(verdict, Verdict::Correct | Verdict::AlreadySolved) {
+        bail!("Answer {} was not accepted", answer);
+    }
+
+    Ok(())
+}
+
+fn time(days: Vec<u32>) -> Result<()> {
+    let days = if days.is_empty() {
+        DAYS.collect()
+    } else {
+        days
+    };
+
+    let mut timings = Vec::new();
+    for day in days {
+        let solver = solver(day, &[])?;
+        let content = match load_input(day, None) {
+            Ok(content) => content,
+            Err(e) => {
+                warn!(day, "Skipping: {:#}", e);
+                continue;
             }
-        })
-        .collect();
-
-    let keys: Vec<KeyLocks> = kls
-        .iter()
-        .filter(|x| matches!(x, KeyLocks::Key(_)))
-        .cloned()
-        .collect();
-    let locks: Vec<KeyLocks> = kls
-        .iter()
-        .filter(|x| matches!(x, KeyLocks::Lock(_)))
-        .cloned()
-        .collect();
-
-    debug!(
-        key_len = keys.len(),
-        lock_len = locks.len(),
-        "Setup Complete"
+        };
+
+        let _span = info_span!("time", day).entered();
+        timings.push(time_day(day, solver.as_ref(), &content)?);
+    }
+
+    println!("{}", Summary(timings));
+    Ok(())
+}
+
+fn bench_day(
+    day: u32,
+    repeats: usize,
+    input: Option<String>,
+    params: &[(String, String)],
+) -> Result<()> {
+    if repeats == 0 {
+        bail!("Need at least one repeat");
+    }
+    let solver = solver(day, params)?;
+    let content = load_input(day, input)?;
+
+    let _span = info_span!("bench", day).entered();
+    println!(
+        "Day {}, {}",
+        day,
+        bench(solver.as_ref(), &content, repeats)?
     );
+    Ok(())
+}
+
+fn fetch(day: u32) -> Result<()> {
+    if !DAYS.contains(&day) {
+        bail!("There is no day {}", day);
+    }
+    let path = InputCache::new(INPUT_DIR, Site::from_env()).resolve(day)?;
+    println!("{}", path.display());
+    Ok(())
+}
+
+fn list() {
+    for day in DAYS {
+        let params = day_params(day);
+        if params.is_empty() {
+            println!("day{}", day);
+            continue;
+        }
+
+        let defaults = params
+            .iter()
+            .map(|p| format!("{}={}", p.name, p.default))
+            .join(" ");
+        println!("day{} {}", day, defaults);
+        for p in params {
+            println!("    {:<12} {}", p.name, p.help);
+        }
+    }
+}
+
+fn check_answers(day: Option<u32>, record: bool) -> Result<()> {
+    let dir = Path::new(INPUT_DIR);
+    let filename = dir.join(ANSWERS_FILE);
+    let content = fs::read_to_string(&filename)
+        .with_context(|| format!("Couldn't read answers {}", filename.display()))?;
+    let answers = parse_answers(&content)?;
+
+    let report = check(dir, &answers, day);
+    println!("{}", report);
 
-    let mut total = 0;
-    for key in keys.iter().cloned() {
-        for lock in locks.iter().cloned() {
-            if let KeyLocks::Key(ref key_list) = key {
-                if let KeyLocks::Lock(ref lock_list) = lock {
-                    let mut okay = true;
-                    for (key_v, lock_v) in zip(key_list, lock_list) {
-                        if key_v + lock_v > 5 {
-                            debug!(key_v, lock_v, "Failed");
-                            okay = false;
-                            break;
-                        }
-                    }
-                    if okay {
-                        total += 1;
-                    }
-                }
+    if record {
+        let lines = record_missing(dir, &answers, day)?;
+        if !lines.is_empty() {
+            let mut file = OpenOptions::new().append(true).open(&filename)?;
+            for line in &lines {
+                println!("Recorded {}", line);
+                writeln!(file, "{}", line)?;
             }
         }
     }
 
-    info!(total, "Done");
+    if !report.failures.is_empty() {
+        bail!("{} recorded answers changed", report.failures.len());
+    }
+
+    Ok(())
+}
+
+fn draw_circuit(
+    input: Option<String>,
+    format: Format,
+    repair: bool,
+    highlight: &[String],
+) -> Result<()> {
+    let circuit = Circuit::parse(&load_input(24, input)?)?;
+
+    let mut wires = HashSet::new();
+    for name in highlight {
+        wires.insert(
+            circuit
+                .wire(name)
+                .with_context(|| format!("There is no wire {}", name))?,
+        );
+    }
+    if repair {
+        for r in adder::repair(&circuit)? {
+            wires.extend([r.swap.0, r.swap.1]);
+        }
+    }
+
+    print!("{}", render(&circuit, format, &wires));
+    Ok(())
+}
+
+fn debug_machine(
+    input: Option<String>,
+    a: Option<u64>,
+    disassemble_only: bool,
+    debugger: impl FnOnce(Machine) -> Debugger,
+) -> Result<()> {
+    let mut machine = Machine::parse(&load_input(17, input)?)?;
+    if let Some(a) = a {
+        machine = machine.with_a(a);
+    }
+
+    if disassemble_only {
+        for line in disassemble(&machine.program) {
+            println!("{}", line);
+        }
+        return Ok(());
+    }
+
+    debugger(machine).run(&mut io::stdin().lock(), &mut io::stdout().lock())
+}
+
+/// How to show the warehouse: its state after the last step, or an animation of every step.
+enum WarehouseView {
+    Last,
+    Terminal {
+        fps: f64,
+    },
+    Gif {
+        path: String,
+        fps: f64,
+        scale: usize,
+    },
+}
+
+fn show_warehouse(
+    input: Option<String>,
+    wide: bool,
+    steps: Option<usize>,
+    view: WarehouseView,
+) -> Result<()> {
+    let plan = RobotPlan::parse(&load_input(15, input)?)?;
+    let steps = steps.unwrap_or(plan.directions.len());
+    match view {
+        WarehouseView::Last => {}
+        WarehouseView::Terminal { fps } => {
+            return animation::play(&plan, wide, steps, fps, &mut io::stdout().lock());
+        }
+        WarehouseView::Gif { path, fps, scale } => {
+            animation::write_gif(&plan, wide, steps, fps, scale, Path::new(&path))?;
+            println!("Wrote {} frames to {}", steps + 1, path);
+            return Ok(());
+        }
+    }
+
+    let mut warehouse = Warehouse::new(&plan.layout, wide)?;
+    warehouse.seek(&plan.directions, steps)?;
+
+    println!("{}", warehouse);
+    print!("Step {} of {}", steps, plan.directions.len());
+    if let Some(last) = warehouse.log().last() {
+        let arrow = last.direction.arrow().unwrap_or('?');
+        if last.blocked {
+            print!(", {} was blocked", arrow);
+        } else {
+            print!(", {} pushed {} boxes", arrow, last.boxes.len());
+        }
+    }
+    println!(
+        ", robot at {:?}, GPS {}",
+        warehouse.robot(),
+        warehouse.gps()
+    );
+    Ok(())
+}
+
+fn draw(
+    day: u32,
+    output: &str,
+    input: Option<String>,
+    format: Option<ImageFormat>,
+    scale: usize,
+    palette: &str,
+    params: &[(String, String)],
+) -> Result<()> {
+    if !PICTURE_DAYS.contains(&day) {
+        bail!(
+            "Day {} has no picture to draw, try one of {}",
+            day,
+            PICTURE_DAYS.iter().join(", ")
+        );
+    }
+    if scale == 0 {
+        bail!("Scale must be at least 1");
+    }
+    let palette = Palette::parse(palette)?;
+    let content = load_input(day, input)?;
+    let image = Image {
+        cells: picture(day, params, &content, palette.len())?,
+        palette,
+        scale,
+    };
+    image.save(Path::new(output), format)?;
+    println!("Wrote {}", output);
+    Ok(())
+}
+
+fn show_keypad(code: &str, robots: usize, limit: usize) -> Result<()> {
+    let mut chain = KeypadChain::door(robots);
+    let sequence = chain.sequence(code, limit)?;
+    println!("{}", sequence);
 
+    let typed = chain.replay(&sequence)?;
+    if typed != code {
+        bail!("Replaying the sequence typed {} instead of {}", typed, code);
+    }
+    println!("{} presses, replayed to type {}", sequence.len(), typed);
     Ok(())
 }
 
@@ -108,8 +536,80 @@ fn main() -> Result<()> {
         .init();
     info!("Tracing Setup");
 
-    day25("./inputs/day25small.txt", false).context("Big Example")?;
-    day25("./inputs/day25.txt", false).context("Big Example")?;
+    let cli = Cli::parse();
+
+    match cli.command {
+        Command::Run {
+            day,
+            part,
+            input,
+            params,
+        } => run(day, part, input, &params).with_context(|| format!("Day {} failed", day))?,
+        Command::Submit { day, part, params } => {
+            submit(day, part, &params).with_context(|| format!("Day {} failed", day))?
+        }
+        Command::Time { days } => time(days)?,
+        Command::Bench {
+            day,
+            repeats,
+            input,
+            params,
+        } => bench_day(day, repeats, input, &params)
+            .with_context(|| format!("Day {} failed", day))?,
+        Command::Fetch { day } => fetch(day).with_context(|| format!("Day {} failed", day))?,
+        Command::List => list(),
+        Command::Circuit {
+            input,
+            format,
+            repair,
+            highlight,
+        } => draw_circuit(input, format, repair, &highlight)?,
+        Command::Machine {
+            input,
+            a,
+            disassemble,
+            trace,
+            break_ips,
+            break_outputs,
+        } => debug_machine(input, a, disassemble, |machine| Debugger {
+            machine,
+            trace,
+            break_ips,
+            break_outputs,
+        })?,
+        Command::Warehouse {
+            input,
+            wide,
+            steps,
+            animate,
+            gif,
+            fps,
+            scale,
+        } => {
+            let view = match (gif, animate) {
+                (Some(path), _) => WarehouseView::Gif { path, fps, scale },
+                (None, true) => WarehouseView::Terminal { fps },
+                (None, false) => WarehouseView::Last,
+            };
+            show_warehouse(input, wide, steps, view)?
+        }
+        Command::Keypad {
+            code,
+            robots,
+            limit,
+        } => show_keypad(&code, robots, limit)?,
+        Command::Draw {
+            day,
+            output,
+            input,
+            format,
+            scale,
+            palette,
+            params,
+        } => draw(day, &output, input, format, scale, &palette, &params)
+            .with_context(|| format!("Day {} failed", day))?,
+        Command::Check { day, record } => check_answers(day, record)?,
+    };
 
     Ok(())
 }
@@ -0,0 +1,101 @@
+//! A tiny HTTP stand-in for the puzzle website, used by the tests of the code that talks to it.
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    thread::{self, JoinHandle},
+};
+
+/// A request as seen by the server.
+#[derive(Debug)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Serves one canned `(status, body)` response per connection, in order, then stops.
+pub struct MockServer {
+    pub url: String,
+    handle: JoinHandle<Vec<Request>>,
+}
+
+impl MockServer {
+    pub fn start(responses: Vec<(u16, &str)>) -> MockServer {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("bind mock server");
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let responses: Vec<(u16, String)> = responses
+            .into_iter()
+            .map(|(status, body)| (status, body.to_string()))
+            .collect();
+
+        let handle = thread::spawn(move || {
+            let mut requests = Vec::new();
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().expect("accept connection");
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let mut parts = line.split_whitespace();
+                let method = parts.next().unwrap_or_default().to_string();
+                let path = parts.next().unwrap_or_default().to_string();
+
+                let mut headers = Vec::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some((k, v)) = line.split_once(':') {
+                        headers.push((k.trim().to_string(), v.trim().to_string()));
+                    }
+                }
+
+                let length = headers
+                    .iter()
+                    .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+                    .map(|(_, v)| v.parse().unwrap())
+                    .unwrap_or(0);
+                let mut content = vec![0; length];
+                reader.read_exact(&mut content).unwrap();
+
+                write!(
+                    stream,
+                    "HTTP/1.1 {} Mock\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+
+                requests.push(Request {
+                    method,
+                    path,
+                    headers,
+                    body: String::from_utf8(content).unwrap(),
+                });
+            }
+            requests
+        });
+
+        MockServer { url, handle }
+    }
+
+    /// Wait for every canned response to be served and return the requests that were made.
+    pub fn finish(self) -> Vec<Request> {
+        self.handle.join().expect("mock server panicked")
+    }
+}
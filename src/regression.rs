@@ -0,0 +1,232 @@
+use std::{fmt, fs, path::Path};
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use tracing::{debug, info_span, warn};
+
+use crate::solution::{DAYS, Part, parse_param, solver};
+
+/// Name of the recorded answers file inside the inputs directory.
+pub const ANSWERS_FILE: &str = "answers.txt";
+
+/// A known answer for one part of a day on one input.
+#[derive(Debug)]
+pub struct Recorded {
+    pub line: usize,
+    pub day: u32,
+    pub part: Part,
+    pub input: String,
+    pub answer: String,
+    pub params: Vec<(String, String)>,
+}
+
+/// Parse the recorded answers. Each non-empty line that isn't a `#` comment is
+/// `day part input answer [name=value ...]`.
+pub fn parse_answers(content: &str) -> Result<Vec<Recorded>> {
+    let mut answers = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 4 {
+            bail!(
+                "Line {}: expected `day part input answer`, got {:?}",
+                i + 1,
+                line
+            );
+        }
+
+        let day = fields[0]
+            .parse()
+            .with_context(|| format!("Line {}: invalid day {}", i + 1, fields[0]))?;
+        let part = Part::from_str(fields[1], true)
+            .map_err(anyhow::Error::msg)
+            .with_context(|| format!("Line {}: invalid part {}", i + 1, fields[1]))?;
+        let params = fields[4..]
+            .iter()
+            .map(|p| parse_param(p))
+            .collect::<Result<_>>()
+            .with_context(|| format!("Line {}: invalid parameter", i + 1))?;
+
+        answers.push(Recorded {
+            line: i + 1,
+            day,
+            part,
+            input: fields[2].to_string(),
+            answer: fields[3].to_string(),
+            params,
+        });
+    }
+
+    Ok(answers)
+}
+
+/// A recorded answer that no longer matches what the day returns.
+pub struct Failure<'a> {
+    pub recorded: &'a Recorded,
+    pub actual: String,
+}
+
+impl fmt::Display for Failure<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let r = self.recorded;
+        writeln!(
+            f,
+            "Day {} part {} on {} (line {})",
+            r.day, r.part, r.input, r.line
+        )?;
+        writeln!(f, "- {}", r.answer)?;
+        write!(f, "+ {}", self.actual)
+    }
+}
+
+#[derive(Default)]
+pub struct Report<'a> {
+    pub passed: usize,
+    pub skipped: Vec<&'a Recorded>,
+    pub failures: Vec<Failure<'a>>,
+}
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for failure in &self.failures {
+            writeln!(f, "{}", failure)?;
+        }
+        write!(
+            f,
+            "{} passed, {} failed, {} skipped (missing input)",
+            self.passed,
+            self.failures.len(),
+            self.skipped.len()
+        )
+    }
+}
+
+/// Run every recorded answer (optionally only those for `day`) against the inputs in `dir`.
+/// Answers whose input file is missing are skipped rather than failed.
+pub fn check<'a>(dir: &Path, answers: &'a [Recorded], day: Option<u32>) -> Report<'a> {
+    let mut report = Report::default();
+
+    for recorded in answers {
+        if day.is_some_and(|d| d != recorded.day) {
+            continue;
+        }
+
+        let path = dir.join(&recorded.input);
+        if !path.exists() {
+            debug!(input = ?path, "Skipping missing input");
+            report.skipped.push(recorded);
+            continue;
+        }
+
+        let _span = info_span!("check", day = recorded.day, part = %recorded.part).entered();
+        let actual = fs::read_to_string(&path)
+            .with_context(|| format!("Couldn't read input {}", path.display()))
+            .and_then(|content| {
+                solver(recorded.day, &recorded.params)?.solve(&content, recorded.part)
+            });
+
+        match actual {
+            Ok(answer) if answer.to_string() == recorded.answer => report.passed += 1,
+            Ok(answer) => report.failures.push(Failure {
+                recorded,
+                actual: answer.to_string(),
+            }),
+            Err(e) => report.failures.push(Failure {
+                recorded,
+                actual: format!("error: {:#}", e),
+            }),
+        }
+    }
+
+    report
+}
+
+/// Answer lines for the full `dayN.txt` inputs in `dir` (optionally only for `day`) that have no
+/// recorded answer yet. Parts that fail to solve are logged and left out.
+pub fn record_missing(dir: &Path, answers: &[Recorded], day: Option<u32>) -> Result<Vec<String>> {
+    let mut lines = Vec::new();
+
+    for d in DAYS.filter(|d| day.is_none_or(|day| day == *d)) {
+        let input = format!("day{}.txt", d);
+        let path = dir.join(&input);
+        if !path.exists() {
+            continue;
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Couldn't read input {}", path.display()))?;
+
+        for part in Part::value_variants() {
+            if answers
+                .iter()
+                .any(|r| r.day == d && r.part == *part && r.input == input)
+            {
+                continue;
+            }
+            let _span = info_span!("record", day = d, part = %part).entered();
+            match solver(d, &[])?.solve(&content, *part) {
+                Ok(answer) => lines.push(format!("{} {} {} {}", d, part, input, answer)),
+                Err(e) => warn!("Not recording: {:#}", e),
+            }
+        }
+    }
+
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn inputs_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("inputs")
+    }
+
+    #[test]
+    fn parses_answer_lines() {
+        let answers = parse_answers("# comment\n\n14 a day14small.txt 12 rows=7 cols=11\n")
+            .expect("valid answers");
+        assert_eq!(answers.len(), 1);
+        let a = &answers[0];
+        assert_eq!((a.line, a.day, a.part), (3, 14, Part::A));
+        assert_eq!(
+            (a.input.as_str(), a.answer.as_str()),
+            ("day14small.txt", "12")
+        );
+        assert_eq!(a.params.len(), 2);
+
+        assert!(parse_answers("1 c day1small.txt 11").is_err());
+        assert!(parse_answers("1 a day1small.txt").is_err());
+    }
+
+    #[test]
+    fn recorded_answers_match() {
+        let dir = inputs_dir();
+        let content = fs::read_to_string(dir.join(ANSWERS_FILE)).expect("answers file");
+        let answers = parse_answers(&content).expect("valid answers");
+
+        let report = check(&dir, &answers, None);
+        assert!(report.failures.is_empty(), "\n{}", report);
+        assert!(report.passed > 0, "No recorded answers were checked");
+    }
+
+    #[test]
+    fn records_unanswered_full_inputs() {
+        let dir = std::env::temp_dir().join(format!("advent24-record-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::copy(inputs_dir().join("day1small.txt"), dir.join("day1.txt")).unwrap();
+
+        let answers = parse_answers("1 a day1.txt 11").unwrap();
+        let lines = record_missing(&dir, &answers, None).unwrap();
+        assert_eq!(lines, ["1 b day1.txt 31"]);
+        assert!(record_missing(&dir, &answers, Some(2)).unwrap().is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,262 @@
+//! Images of grid states as PPM, PNG or SVG files, so pictures of a puzzle can be saved instead
+//! of printed to the terminal.
+//!
+//! A grid to draw holds a palette index per cell. Indices past the end of the palette wrap round,
+//! so a picture can still be drawn with fewer colours than it was made for.
+
+use std::{fmt::Write as _, fs, path::Path};
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+
+use crate::util::Grid;
+
+pub type Rgb = [u8; 3];
+
+/// Palette index of empty space.
+pub const BACKGROUND: usize = 0;
+/// Palette index of walls.
+pub const WALL: usize = 1;
+/// Palette index of whatever the picture is about: paths, robots and so on.
+pub const HIGHLIGHT: usize = 2;
+
+pub const DEFAULT_PALETTE: &str = "#1e1e2e,#6c7086,#f9e2af,#f38ba8,#a6e3a1,#89b4fa,#fab387,\
+                                   #cba6f7,#94e2d5,#eba0ac,#74c7ec,#b4befe";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+    Svg,
+}
+
+impl ImageFormat {
+    /// The format matching a file's extension.
+    pub fn from_path(path: &Path) -> Result<ImageFormat> {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("ppm") => Ok(ImageFormat::Ppm),
+            Some("png") => Ok(ImageFormat::Png),
+            Some("svg") => Ok(ImageFormat::Svg),
+            _ => bail!(
+                "Can't tell the image format of {}, expected .ppm, .png or .svg",
+                path.display()
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palette(Vec<Rgb>);
+
+impl Palette {
+    /// Parse comma separated `#rrggbb` colours.
+    pub fn parse(colours: &str) -> Result<Palette> {
+        let parse_one = |colour: &str| -> Result<Rgb> {
+            let hex = colour
+                .trim()
+                .strip_prefix('#')
+                .filter(|h| h.len() == 6 && h.chars().all(|c| c.is_ascii_hexdigit()))
+                .with_context(|| format!("Expected a colour like #1e1e2e, not {:?}", colour))?;
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16);
+            Ok([channel(0)?, channel(2)?, channel(4)?])
+        };
+        let colours = colours
+            .split(',')
+            .map(parse_one)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Palette(colours))
+    }
+
+    /// Number of colours, which is never zero.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn colour(&self, index: usize) -> Rgb {
+        self.0[index % self.0.len()]
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Palette {
+        Palette::parse(DEFAULT_PALETTE).expect("Default palette is valid")
+    }
+}
+
+/// A grid of palette indices, drawn with every cell a `scale` pixel square.
+pub struct Image {
+    pub cells: Grid<usize>,
+    pub palette: Palette,
+    pub scale: usize,
+}
+
+impl Image {
+    fn size(&self) -> (usize, usize) {
+        (
+            self.cells.cols() as usize * self.scale,
+            self.cells.rows() as usize * self.scale,
+        )
+    }
+
+    /// RGB bytes, row by row.
+    fn pixels(&self) -> Vec<u8> {
+        let mut pixels = Vec::new();
+        for row in self.cells.iter_rows() {
+            let line: Vec<u8> = row
+                .iter()
+                .flat_map(|i| {
+                    let rgb = self.palette.colour(*i);
+                    std::iter::repeat_n(rgb, self.scale).flatten()
+                })
+                .collect();
+            for _ in 0..self.scale {
+                pixels.extend_from_slice(&line);
+            }
+        }
+        pixels
+    }
+
+    /// A binary `P6` portable pixmap.
+    pub fn ppm(&self) -> Vec<u8> {
+        let (width, height) = self.size();
+        let mut out = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+        out.extend(self.pixels());
+        out
+    }
+
+    pub fn png(&self) -> Result<Vec<u8>> {
+        let (width, height) = self.size();
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels())?;
+        writer.finish()?;
+        Ok(out)
+    }
+
+    /// One unit per cell, with a rectangle for each run of same coloured cells in a row.
+    pub fn svg(&self) -> String {
+        let (width, height) = self.size();
+        let (rows, cols) = (self.cells.rows(), self.cells.cols());
+        let hex = |i: usize| {
+            let [r, g, b] = self.palette.colour(i);
+            format!("#{:02x}{:02x}{:02x}", r, g, b)
+        };
+
+        let mut out = String::new();
+        writeln!(
+            out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" \
+             viewBox=\"0 0 {} {}\" shape-rendering=\"crispEdges\">",
+            width, height, cols, rows
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "  <rect width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+            cols,
+            rows,
+            hex(BACKGROUND)
+        )
+        .unwrap();
+
+        let background = self.palette.colour(BACKGROUND);
+        for (r, row) in self.cells.iter_rows().enumerate() {
+            let mut c = 0;
+            for run in row.chunk_by(|a, b| self.palette.colour(*a) == self.palette.colour(*b)) {
+                if self.palette.colour(run[0]) != background {
+                    writeln!(
+                        out,
+                        "  <rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"1\" fill=\"{}\"/>",
+                        c,
+                        r,
+                        run.len(),
+                        hex(run[0])
+                    )
+                    .unwrap();
+                }
+                c += run.len();
+            }
+        }
+        writeln!(out, "</svg>").unwrap();
+        out
+    }
+
+    pub fn encode(&self, format: ImageFormat) -> Result<Vec<u8>> {
+        Ok(match format {
+            ImageFormat::Ppm => self.ppm(),
+            ImageFormat::Png => self.png()?,
+            ImageFormat::Svg => self.svg().into_bytes(),
+        })
+    }
+
+    /// Write the image in `format`, or the format its extension names.
+    pub fn save(&self, path: &Path, format: Option<ImageFormat>) -> Result<()> {
+        let format = match format {
+            Some(format) => format,
+            None => ImageFormat::from_path(path)?,
+        };
+        fs::write(path, self.encode(format)?)
+            .with_context(|| format!("Couldn't write {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(scale: usize) -> Image {
+        let cells = Grid::from_rows(vec![vec![0, 2, 2], vec![1, 0, 14]]).unwrap();
+        Image {
+            cells,
+            palette: Palette::parse("#000000,#ffffff,#ff0000").unwrap(),
+            scale,
+        }
+    }
+
+    #[test]
+    fn writes_every_format() {
+        let ppm = image(1).ppm();
+        assert!(ppm.starts_with(b"P6\n3 2\n255\n"));
+        assert_eq!(ppm.len(), 11 + 3 * 2 * 3);
+        assert_eq!(&ppm[11..17], &[0, 0, 0, 255, 0, 0]);
+        // Index 14 wraps round to red
+        assert_eq!(&ppm[ppm.len() - 3..], &[255, 0, 0]);
+        assert!(image(2).ppm().starts_with(b"P6\n6 4\n255\n"));
+
+        let svg = image(4).svg();
+        assert!(svg.contains("width=\"12\" height=\"8\" viewBox=\"0 0 3 2\""));
+        assert!(svg.contains("<rect x=\"1\" y=\"0\" width=\"2\" height=\"1\" fill=\"#ff0000\"/>"));
+        assert!(svg.contains("<rect x=\"0\" y=\"1\" width=\"1\" height=\"1\" fill=\"#ffffff\"/>"));
+        assert_eq!(svg.matches("<rect").count(), 4);
+
+        let png = image(3).png().unwrap();
+        let mut reader = png::Decoder::new(std::io::Cursor::new(png))
+            .read_info()
+            .unwrap();
+        let mut buf = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut buf).unwrap();
+        assert_eq!((info.width, info.height), (9, 6));
+        assert_eq!(&buf[..buf.len() / 2], &image(3).pixels()[..buf.len() / 2]);
+    }
+
+    #[test]
+    fn parses_palettes_and_formats() {
+        assert_eq!(Palette::default().colour(BACKGROUND), [0x1e, 0x1e, 0x2e]);
+        assert!(Palette::parse("#12345").is_err());
+        assert!(Palette::parse("#12345g").is_err());
+        // Six bytes, but not six hex digits
+        assert!(Palette::parse("#aé€").is_err());
+        assert_eq!(
+            ImageFormat::from_path(Path::new("tree.PNG")).unwrap(),
+            ImageFormat::Png
+        );
+        assert!(ImageFormat::from_path(Path::new("tree.jpg")).is_err());
+    }
+}
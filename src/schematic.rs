@@ -0,0 +1,368 @@
+//! Drawings of a `Circuit` as Graphviz DOT or Mermaid flowcharts, for checking the day 24 adder
+//! by eye.
+//!
+//! Gates become nodes shaped by their operation, with an edge per wire they read labelled with the
+//! wire's name. Constants and wires nobody reads get a node of their own, grouped by bus in bit
+//! order. Highlighted wires are drawn in red, along with the gate driving them.
+
+use std::{collections::HashSet, fmt::Write};
+
+use clap::ValueEnum;
+
+use crate::circuit::{Circuit, Gate, WireId};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Dot,
+    Mermaid,
+}
+
+const HIGHLIGHT: &str = "#cc0000";
+const HIGHLIGHT_FILL: &str = "#f4cccc";
+
+fn gate_id(gate: usize) -> String {
+    format!("gate_{}", gate)
+}
+
+fn op_name(op: Gate) -> &'static str {
+    match op {
+        Gate::And => "AND",
+        Gate::Or => "OR",
+        Gate::Xor => "XOR",
+    }
+}
+
+/// A gate, or a wire drawn as a node of its own.
+#[derive(Clone, Copy)]
+enum Node {
+    Gate(usize),
+    Wire(WireId),
+}
+
+/// An edge carrying `wire` between two nodes. `label` is false when one end is the wire's own node.
+struct Edge {
+    from: Node,
+    to: Node,
+    wire: WireId,
+    label: bool,
+}
+
+/// The parts of the drawing shared by both formats.
+struct Layout {
+    /// Wire nodes of each bus in bit order, then the ones on no bus.
+    groups: Vec<(Option<char>, Vec<WireId>)>,
+    edges: Vec<Edge>,
+}
+
+fn layout(circuit: &Circuit) -> Layout {
+    let mut read = vec![false; circuit.wire_count()];
+    for gate in circuit.gates() {
+        read[gate.left] = true;
+        read[gate.right] = true;
+    }
+    let has_node = |w: WireId| circuit.driver(w).is_none() || !read[w];
+    let source = |w: WireId| match circuit.driver(w) {
+        Some(g) => Node::Gate(g),
+        None => Node::Wire(w),
+    };
+
+    let mut groups: Vec<(Option<char>, Vec<WireId>)> = Vec::new();
+    let mut on_bus: HashSet<WireId> = HashSet::new();
+    for bus in circuit.bus_names() {
+        let wires: Vec<WireId> = circuit
+            .bus(bus)
+            .iter()
+            .copied()
+            .filter(|w| has_node(*w))
+            .collect();
+        on_bus.extend(wires.iter().copied());
+        if !wires.is_empty() {
+            groups.push((Some(bus), wires));
+        }
+    }
+    let loose: Vec<WireId> = (0..circuit.wire_count())
+        .filter(|w| has_node(*w) && !on_bus.contains(w))
+        .collect();
+    if !loose.is_empty() {
+        groups.push((None, loose));
+    }
+
+    let mut edges = Vec::new();
+    for (g, gate) in circuit.gates().iter().enumerate() {
+        for wire in [gate.left, gate.right] {
+            edges.push(Edge {
+                from: source(wire),
+                to: Node::Gate(g),
+                wire,
+                label: circuit.driver(wire).is_some(),
+            });
+        }
+        if !read[gate.out] {
+            edges.push(Edge {
+                from: Node::Gate(g),
+                to: Node::Wire(gate.out),
+                wire: gate.out,
+                label: false,
+            });
+        }
+    }
+
+    Layout { groups, edges }
+}
+
+/// Draw the circuit, marking the `highlight` wires.
+pub fn render(circuit: &Circuit, format: Format, highlight: &HashSet<WireId>) -> String {
+    match format {
+        Format::Dot => dot(circuit, highlight),
+        Format::Mermaid => mermaid(circuit, highlight),
+    }
+}
+
+fn dot(circuit: &Circuit, highlight: &HashSet<WireId>) -> String {
+    let layout = layout(circuit);
+    let id = |node: Node| match node {
+        Node::Gate(g) => gate_id(g),
+        Node::Wire(w) => circuit.name(w).to_string(),
+    };
+    let mut out = String::new();
+
+    writeln!(out, "digraph circuit {{").unwrap();
+    writeln!(out, "    rankdir=LR;").unwrap();
+    writeln!(out, "    node [fontname=\"monospace\"];").unwrap();
+
+    for (bus, wires) in &layout.groups {
+        let indent = match bus {
+            Some(bus) => {
+                writeln!(out, "    subgraph cluster_{} {{", bus).unwrap();
+                writeln!(out, "        label=\"{}\";", bus).unwrap();
+                "        "
+            }
+            None => "    ",
+        };
+        for wire in wires {
+            let colour = if highlight.contains(wire) {
+                format!(", color=\"{}\", fontcolor=\"{}\"", HIGHLIGHT, HIGHLIGHT)
+            } else {
+                String::new()
+            };
+            writeln!(
+                out,
+                "{}\"{}\" [shape=plaintext{}];",
+                indent,
+                circuit.name(*wire),
+                colour
+            )
+            .unwrap();
+        }
+        if bus.is_some() {
+            writeln!(out, "    }}").unwrap();
+        }
+    }
+
+    for (g, gate) in circuit.gates().iter().enumerate() {
+        let shape = match gate.op {
+            Gate::And => "box",
+            Gate::Or => "ellipse",
+            Gate::Xor => "hexagon",
+        };
+        let fill = if highlight.contains(&gate.out) {
+            format!(
+                ", style=filled, fillcolor=\"{}\", color=\"{}\"",
+                HIGHLIGHT_FILL, HIGHLIGHT
+            )
+        } else {
+            String::new()
+        };
+        writeln!(
+            out,
+            "    \"{}\" [label=\"{}\", shape={}{}];",
+            gate_id(g),
+            op_name(gate.op),
+            shape,
+            fill
+        )
+        .unwrap();
+    }
+
+    for edge in &layout.edges {
+        let mut attrs: Vec<String> = Vec::new();
+        if edge.label {
+            attrs.push(format!("label=\"{}\"", circuit.name(edge.wire)));
+        }
+        if highlight.contains(&edge.wire) {
+            attrs.push(format!(
+                "color=\"{}\", fontcolor=\"{}\", penwidth=2",
+                HIGHLIGHT, HIGHLIGHT
+            ));
+        }
+        let attrs = if attrs.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", attrs.join(", "))
+        };
+        writeln!(
+            out,
+            "    \"{}\" -> \"{}\"{};",
+            id(edge.from),
+            id(edge.to),
+            attrs
+        )
+        .unwrap();
+    }
+
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+fn mermaid(circuit: &Circuit, highlight: &HashSet<WireId>) -> String {
+    let layout = layout(circuit);
+    // Wire names are prefixed so none of them can clash with a keyword like `end`
+    let id = |node: Node| match node {
+        Node::Gate(g) => gate_id(g),
+        Node::Wire(w) => format!("w_{}", circuit.name(w)),
+    };
+    let mut out = String::new();
+
+    writeln!(out, "flowchart LR").unwrap();
+    for (bus, wires) in &layout.groups {
+        let indent = match bus {
+            Some(bus) => {
+                writeln!(out, "    subgraph bus_{}[{}]", bus, bus).unwrap();
+                "        "
+            }
+            None => "    ",
+        };
+        for wire in wires {
+            writeln!(
+                out,
+                "{}{}[{}]",
+                indent,
+                id(Node::Wire(*wire)),
+                circuit.name(*wire)
+            )
+            .unwrap();
+        }
+        if bus.is_some() {
+            writeln!(out, "    end").unwrap();
+        }
+    }
+
+    for (g, gate) in circuit.gates().iter().enumerate() {
+        let (open, close) = match gate.op {
+            Gate::And => ("[", "]"),
+            Gate::Or => ("([", "])"),
+            Gate::Xor => ("{{", "}}"),
+        };
+        writeln!(
+            out,
+            "    {}{}{}{}",
+            gate_id(g),
+            open,
+            op_name(gate.op),
+            close
+        )
+        .unwrap();
+    }
+
+    let mut marked: Vec<String> = Vec::new();
+    for (i, edge) in layout.edges.iter().enumerate() {
+        if edge.label {
+            writeln!(
+                out,
+                "    {} -->|{}| {}",
+                id(edge.from),
+                circuit.name(edge.wire),
+                id(edge.to)
+            )
+            .unwrap();
+        } else {
+            writeln!(out, "    {} --> {}", id(edge.from), id(edge.to)).unwrap();
+        }
+        if highlight.contains(&edge.wire) {
+            marked.push(i.to_string());
+        }
+    }
+
+    if !highlight.is_empty() {
+        writeln!(
+            out,
+            "    classDef swapped fill:{},stroke:{}",
+            HIGHLIGHT_FILL, HIGHLIGHT
+        )
+        .unwrap();
+        let has_node = |w: &WireId| layout.groups.iter().any(|(_, wires)| wires.contains(w));
+        let mut nodes: Vec<String> = Vec::new();
+        for wire in highlight {
+            if let Some(g) = circuit.driver(*wire) {
+                nodes.push(gate_id(g));
+            }
+            if has_node(wire) {
+                nodes.push(id(Node::Wire(*wire)));
+            }
+        }
+        nodes.sort();
+        writeln!(out, "    class {} swapped", nodes.join(",")).unwrap();
+    }
+    if !marked.is_empty() {
+        writeln!(
+            out,
+            "    linkStyle {} stroke:{},stroke-width:3px",
+            marked.join(","),
+            HIGHLIGHT
+        )
+        .unwrap();
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL: &str = "x00: 1\ny00: 0\n\nx00 XOR y00 -> z00\nx00 AND y00 -> z01\n";
+
+    #[test]
+    fn draws_both_formats() {
+        let circuit = Circuit::parse(SMALL).unwrap();
+        let highlight: HashSet<WireId> = [circuit.wire("z01").unwrap()].into();
+
+        let dot = render(&circuit, Format::Dot, &highlight);
+        assert!(dot.contains("subgraph cluster_x {"), "{}", dot);
+        assert!(
+            dot.contains("\"gate_0\" [label=\"XOR\", shape=hexagon];"),
+            "{}",
+            dot
+        );
+        assert!(
+            dot.contains("\"gate_1\" [label=\"AND\", shape=box, style=filled"),
+            "{}",
+            dot
+        );
+        assert!(
+            dot.contains("\"gate_1\" -> \"z01\" [color=\"#cc0000\""),
+            "{}",
+            dot
+        );
+
+        let mermaid = render(&circuit, Format::Mermaid, &highlight);
+        assert!(mermaid.starts_with("flowchart LR\n"), "{}", mermaid);
+        assert!(mermaid.contains("    gate_0{{XOR}}\n"), "{}", mermaid);
+        assert!(mermaid.contains("    w_x00 --> gate_0\n"), "{}", mermaid);
+        assert!(
+            mermaid.contains("    class gate_1,w_z01 swapped\n"),
+            "{}",
+            mermaid
+        );
+        assert!(mermaid.contains("    linkStyle 5 stroke:"), "{}", mermaid);
+    }
+
+    #[test]
+    fn keeps_mermaid_ids_apart_from_keywords() {
+        let circuit = Circuit::parse("end: 1\ny00: 0\n\nend AND y00 -> z00\n").unwrap();
+        let mermaid = render(&circuit, Format::Mermaid, &HashSet::new());
+        assert!(mermaid.contains("    w_end[end]\n"), "{}", mermaid);
+        assert!(mermaid.contains("    w_end --> gate_0\n"), "{}", mermaid);
+        assert!(!mermaid.contains(" end["), "{}", mermaid);
+    }
+}
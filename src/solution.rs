@@ -0,0 +1,353 @@
+use std::{any::Any, fmt, ops::RangeInclusive, str::FromStr};
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use num_bigint::BigUint;
+
+use crate::done::*;
+use crate::render::{BACKGROUND, HIGHLIGHT};
+use crate::swarm::PictureSearch;
+use crate::util::Grid;
+
+/// Days with a solution that can be dispatched from the command line.
+pub const DAYS: RangeInclusive<u32> = 1..=25;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Part {
+    A,
+    B,
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::A => write!(f, "a"),
+            Part::B => write!(f, "b"),
+        }
+    }
+}
+
+/// The answer to one part of a puzzle, as it would be typed into the website.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Number(i64),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(x) => write!(f, "{}", x),
+            Answer::Text(x) => write!(f, "{}", x),
+        }
+    }
+}
+
+/// Numbers too big for an `i64` are given as text, like `BigUint` answers.
+macro_rules! answer_from_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Self {
+                    match i64::try_from(value) {
+                        Ok(x) => Answer::Number(x),
+                        Err(_) => Answer::Text(value.to_string()),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+answer_from_int!(i32, i64, u32, u64, usize);
+
+/// Numbers too big for an `i64` are given as text.
+impl From<BigUint> for Answer {
+    fn from(value: BigUint) -> Self {
+        match i64::try_from(&value) {
+            Ok(x) => Answer::Number(x),
+            Err(_) => Answer::Text(value.to_string()),
+        }
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Answer::Text(value.to_string())
+    }
+}
+
+/// A single day of the puzzle. The input is parsed once and shared by both parts.
+pub trait Solution {
+    type Input;
+
+    fn parse(&self, content: &str) -> Result<Self::Input>;
+    fn part_a(&self, input: &Self::Input) -> Result<Answer>;
+    fn part_b(&self, input: &Self::Input) -> Result<Answer>;
+}
+
+/// Type erased `Solution` so that every day can be stored and dispatched the same way.
+pub trait Solver {
+    fn parse_any(&self, content: &str) -> Result<Box<dyn Any>>;
+    fn solve_any(&self, input: &dyn Any, part: Part) -> Result<Answer>;
+
+    fn solve(&self, content: &str, part: Part) -> Result<Answer> {
+        let input = self.parse_any(content)?;
+        self.solve_any(input.as_ref(), part)
+    }
+}
+
+impl<S> Solver for S
+where
+    S: Solution,
+    S::Input: 'static,
+{
+    fn parse_any(&self, content: &str) -> Result<Box<dyn Any>> {
+        Ok(Box::new(self.parse(content)?))
+    }
+
+    fn solve_any(&self, input: &dyn Any, part: Part) -> Result<Answer> {
+        let input = input
+            .downcast_ref::<S::Input>()
+            .context("Input was parsed by a different day")?;
+        match part {
+            Part::A => self.part_a(input),
+            Part::B => self.part_b(input),
+        }
+    }
+}
+
+/// An extra, day specific, parameter that can be passed with `--param name=value`.
+pub struct Param {
+    pub name: &'static str,
+    pub default: &'static str,
+    pub help: &'static str,
+}
+
+pub fn day_params(day: u32) -> &'static [Param] {
+    match day {
+        13 => &[
+            Param {
+                name: "a_cost",
+                default: "3",
+                help: "Tokens per press of button A",
+            },
+            Param {
+                name: "b_cost",
+                default: "1",
+                help: "Tokens per press of button B",
+            },
+            Param {
+                name: "limit",
+                default: "100",
+                help: "Most presses of each button in part A, 0 for no limit",
+            },
+            Param {
+                name: "offset",
+                default: "10000000000000",
+                help: "Added to both prize coordinates in part B",
+            },
+        ],
+        14 => &[
+            Param {
+                name: "rows",
+                default: "103",
+                help: "Height of the robot area",
+            },
+            Param {
+                name: "cols",
+                default: "101",
+                help: "Width of the robot area",
+            },
+            Param {
+                name: "picture",
+                default: "crt",
+                help: "How to find the picture: crt, variance, cluster, entropy or no-overlap",
+            },
+        ],
+        18 => &[
+            Param {
+                name: "size",
+                default: "70",
+                help: "Largest row/col index of the memory space",
+            },
+            Param {
+                name: "steps",
+                default: "1024",
+                help: "Number of bytes fallen before the first search",
+            },
+            Param {
+                name: "first_jump",
+                default: "64",
+                help: "Initial number of bytes to skip between searches",
+            },
+        ],
+        20 => &[Param {
+            name: "threshold",
+            default: "100",
+            help: "Minimum number of picoseconds a cheat has to save",
+        }],
+        21 => &[Param {
+            name: "robots",
+            default: "25",
+            help: "Robots on directional keypads between you and the door in part B",
+        }],
+        22 => &[Param {
+            name: "steps",
+            default: "2000",
+            help: "Number of secret numbers generated per buyer",
+        }],
+        24 => &[Param {
+            name: "genetic",
+            default: "false",
+            help: "Fall back to a genetic search if the adder can't be repaired bit by bit",
+        }],
+        _ => &[],
+    }
+}
+
+/// Split a `name=value` parameter.
+pub fn parse_param(arg: &str) -> Result<(String, String)> {
+    let (name, value) = arg
+        .split_once('=')
+        .context("Parameters must be given as name=value")?;
+    Ok((name.to_string(), value.to_string()))
+}
+
+/// Look up a day parameter, falling back to the default listed in `day_params`.
+fn param<T>(day: u32, params: &[(String, String)], name: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    let default = day_params(day)
+        .iter()
+        .find(|p| p.name == name)
+        .with_context(|| format!("Day {} has no parameter {}", day, name))?
+        .default;
+
+    let value = params
+        .iter()
+        .rev()
+        .find(|(k, _)| k == name)
+        .map(|(_, v)| v.as_str())
+        .unwrap_or(default);
+
+    value
+        .parse()
+        .with_context(|| format!("Invalid value for {}: {}", name, value))
+}
+
+/// Fail on parameters the day doesn't take.
+fn check_params(day: u32, params: &[(String, String)]) -> Result<()> {
+    for (name, _) in params {
+        if !day_params(day).iter().any(|p| p.name == name) {
+            bail!("Day {} does not take a {} parameter", day, name);
+        }
+    }
+    Ok(())
+}
+
+fn day14(params: &[(String, String)]) -> Result<Day14> {
+    Ok(Day14 {
+        rows: param(14, params, "rows")?,
+        cols: param(14, params, "cols")?,
+        picture: PictureSearch::parse(&param::<String>(14, params, "picture")?)?,
+    })
+}
+
+fn day18(params: &[(String, String)]) -> Result<Day18> {
+    Ok(Day18 {
+        size: param(18, params, "size")?,
+        steps: param(18, params, "steps")?,
+        first_jump: param(18, params, "first_jump")?,
+    })
+}
+
+/// Build the solver for a day, configured with any extra parameters.
+pub fn solver(day: u32, params: &[(String, String)]) -> Result<Box<dyn Solver>> {
+    check_params(day, params)?;
+
+    let solver: Box<dyn Solver> = match day {
+        1 => Box::new(Day1),
+        2 => Box::new(Day2),
+        3 => Box::new(Day3),
+        4 => Box::new(Day4),
+        5 => Box::new(Day5),
+        6 => Box::new(Day6),
+        7 => Box::new(Day7),
+        8 => Box::new(Day8),
+        9 => Box::new(Day9),
+        10 => Box::new(Day10),
+        11 => Box::new(Day11),
+        12 => Box::new(Day12),
+        13 => Box::new(Day13 {
+            a_cost: param(day, params, "a_cost")?,
+            b_cost: param(day, params, "b_cost")?,
+            limit: param(day, params, "limit")?,
+            offset: param(day, params, "offset")?,
+        }),
+        14 => Box::new(day14(params)?),
+        15 => Box::new(Day15),
+        16 => Box::new(Day16),
+        17 => Box::new(Day17),
+        18 => Box::new(day18(params)?),
+        19 => Box::new(Day19),
+        20 => Box::new(Day20 {
+            threshold: param(day, params, "threshold")?,
+        }),
+        21 => Box::new(Day21 {
+            robots: param(day, params, "robots")?,
+        }),
+        22 => Box::new(Day22 {
+            steps: param(day, params, "steps")?,
+        }),
+        23 => Box::new(Day23),
+        24 => Box::new(Day24 {
+            genetic: param(day, params, "genetic")?,
+        }),
+        25 => Box::new(Day25),
+        _ => bail!("Day {} is not implemented", day),
+    };
+
+    Ok(solver)
+}
+
+/// Days with something to draw.
+pub const PICTURE_DAYS: [u32; 4] = [12, 14, 16, 18];
+
+/// Palette indices for a picture of a day's puzzle: the garden regions of day 12, the robots'
+/// picture of day 14, the tiles on the best paths of day 16 and the fallen bytes and path of
+/// day 18. `colours` is the size of the
+/// palette it will be drawn with.
+pub fn picture(
+    day: u32,
+    params: &[(String, String)],
+    content: &str,
+    colours: usize,
+) -> Result<Grid<usize>> {
+    check_params(day, params)?;
+    match day {
+        12 => Ok(garden_picture(&Day12.parse(content)?, colours)),
+        14 => {
+            let day14 = day14(params)?;
+            let swarm = day14.parse(content)?;
+            let time = swarm.picture(day14.picture)?;
+            Ok(swarm
+                .frame(time)
+                .map(|robot| if *robot { HIGHLIGHT } else { BACKGROUND }))
+        }
+        16 => Ok(maze_picture(&Day16.parse(content)?)),
+        18 => {
+            let day18 = day18(params)?;
+            Ok(memory_picture(&day18, &day18.parse(content)?))
+        }
+        _ => bail!("Day {} has no picture to draw", day),
+    }
+}
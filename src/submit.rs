@@ -0,0 +1,440 @@
+use std::{
+    fmt,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result, bail};
+use clap::ValueEnum;
+use regex::Regex;
+use reqwest::header;
+use tracing::{info, instrument, warn};
+
+use crate::{
+    inputs::Site,
+    solution::{Answer, Part},
+};
+
+/// Name of the submission history file inside the inputs directory.
+pub const HISTORY_FILE: &str = "submissions.tsv";
+
+/// Seconds the website makes you wait after a wrong answer.
+const WRONG_ANSWER_WAIT: u64 = 60;
+
+/// What the website said about a submitted answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    Wrong,
+    TooHigh,
+    TooLow,
+    /// Submitted too soon after the last attempt, with the number of seconds left to wait.
+    Wait(u64),
+    /// The part was already solved, so the answer wasn't checked.
+    AlreadySolved,
+}
+
+impl Verdict {
+    /// Classify the HTML returned after posting an answer.
+    pub fn parse(body: &str) -> Result<Verdict> {
+        let wait = Regex::new(r"have (?:(\d+)m )?(\d+)s left to wait").unwrap();
+
+        if body.contains("That's the right answer") {
+            Ok(Verdict::Correct)
+        } else if body.contains("your answer is too high") {
+            Ok(Verdict::TooHigh)
+        } else if body.contains("your answer is too low") {
+            Ok(Verdict::TooLow)
+        } else if body.contains("That's not the right answer") {
+            Ok(Verdict::Wrong)
+        } else if let Some(caps) = wait.captures(body) {
+            let minutes: u64 = caps.get(1).map_or(Ok(0), |m| m.as_str().parse())?;
+            let seconds: u64 = caps[2].parse()?;
+            Ok(Verdict::Wait(minutes * 60 + seconds))
+        } else if body.contains("You gave an answer too recently") {
+            Ok(Verdict::Wait(60))
+        } else if body.contains("Did you already complete it") {
+            Ok(Verdict::AlreadySolved)
+        } else {
+            let snippet: String = body.chars().take(200).collect();
+            bail!("Couldn't understand the response: {}", snippet)
+        }
+    }
+
+    fn tag(&self) -> String {
+        match self {
+            Verdict::Correct => "right".to_string(),
+            Verdict::Wrong => "wrong".to_string(),
+            Verdict::TooHigh => "high".to_string(),
+            Verdict::TooLow => "low".to_string(),
+            Verdict::Wait(s) => format!("wait:{}", s),
+            Verdict::AlreadySolved => "solved".to_string(),
+        }
+    }
+
+    fn from_tag(tag: &str) -> Result<Verdict> {
+        Ok(match tag {
+            "right" => Verdict::Correct,
+            "wrong" => Verdict::Wrong,
+            "high" => Verdict::TooHigh,
+            "low" => Verdict::TooLow,
+            "solved" => Verdict::AlreadySolved,
+            _ => match tag.strip_prefix("wait:") {
+                Some(s) => Verdict::Wait(s.parse()?),
+                None => bail!("Unknown verdict {}", tag),
+            },
+        })
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Correct => write!(f, "right answer"),
+            Verdict::Wrong => write!(f, "wrong answer"),
+            Verdict::TooHigh => write!(f, "too high"),
+            Verdict::TooLow => write!(f, "too low"),
+            Verdict::Wait(s) => write!(f, "wait {}s before trying again", s),
+            Verdict::AlreadySolved => write!(f, "already solved"),
+        }
+    }
+}
+
+/// One submission, as stored in the history file.
+#[derive(Debug, Clone)]
+pub struct Attempt {
+    /// Seconds since the unix epoch.
+    pub time: u64,
+    pub day: u32,
+    pub part: Part,
+    pub answer: String,
+    pub verdict: Verdict,
+}
+
+/// Every answer submitted so far. Stored one tab separated attempt per line:
+/// `time day part answer verdict`.
+pub struct History {
+    path: PathBuf,
+    attempts: Vec<Attempt>,
+}
+
+impl History {
+    pub fn load(path: impl Into<PathBuf>) -> Result<History> {
+        let path = path.into();
+        let mut attempts = Vec::new();
+
+        if path.exists() {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("Couldn't read history {}", path.display()))?;
+            for (i, line) in content.lines().enumerate() {
+                let fields: Vec<&str> = line.split('\t').collect();
+                if fields.len() != 5 {
+                    bail!("{} line {}: expected 5 fields", path.display(), i + 1);
+                }
+                attempts.push(Attempt {
+                    time: fields[0].parse()?,
+                    day: fields[1].parse()?,
+                    part: Part::from_str(fields[2], true).map_err(anyhow::Error::msg)?,
+                    answer: fields[3].to_string(),
+                    verdict: Verdict::from_tag(fields[4])?,
+                });
+            }
+        }
+
+        Ok(History { path, attempts })
+    }
+
+    pub fn attempts(&self, day: u32, part: Part) -> impl Iterator<Item = &Attempt> {
+        self.attempts
+            .iter()
+            .filter(move |a| a.day == day && a.part == part)
+    }
+
+    /// Refuse an answer that the history already shows can't be right, or that would be
+    /// submitted before the last wait has run out.
+    pub fn check(&self, day: u32, part: Part, answer: &str, now: u64) -> Result<()> {
+        let mut lowest_high: Option<i64> = None;
+        let mut highest_low: Option<i64> = None;
+
+        for attempt in self.attempts(day, part) {
+            match attempt.verdict {
+                Verdict::Correct if attempt.answer == answer => {
+                    bail!("{} was already accepted", answer)
+                }
+                Verdict::Correct => {
+                    bail!("Already solved with {}, not {}", attempt.answer, answer)
+                }
+                Verdict::Wrong | Verdict::TooHigh | Verdict::TooLow if attempt.answer == answer => {
+                    bail!(
+                        "{} was already submitted and was {}",
+                        answer,
+                        attempt.verdict
+                    )
+                }
+                Verdict::TooHigh => {
+                    if let Ok(x) = attempt.answer.parse::<i64>() {
+                        lowest_high = Some(lowest_high.map_or(x, |h| h.min(x)));
+                    }
+                }
+                Verdict::TooLow => {
+                    if let Ok(x) = attempt.answer.parse::<i64>() {
+                        highest_low = Some(highest_low.map_or(x, |l| l.max(x)));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Ok(x) = answer.parse::<i64>() {
+            if let Some(high) = lowest_high
+                && x >= high
+            {
+                bail!("{} is not below {}, which was too high", x, high);
+            }
+            if let Some(low) = highest_low
+                && x <= low
+            {
+                bail!("{} is not above {}, which was too low", x, low);
+            }
+        }
+
+        // Any submission for any day counts towards the rate limit, and a wrong answer always
+        // costs at least a minute.
+        if let Some(last) = self.attempts.last() {
+            let wait = match last.verdict {
+                Verdict::Wait(wait) => wait,
+                Verdict::Wrong | Verdict::TooHigh | Verdict::TooLow => WRONG_ANSWER_WAIT,
+                Verdict::Correct | Verdict::AlreadySolved => 0,
+            };
+            if now < last.time + wait {
+                bail!("Still throttled, wait another {}s", last.time + wait - now);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn record(&mut self, attempt: Attempt) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Couldn't open history {}", self.path.display()))?;
+        writeln!(
+            file,
+            "{}\t{}\t{}\t{}\t{}",
+            attempt.time,
+            attempt.day,
+            attempt.part,
+            attempt.answer,
+            attempt.verdict.tag()
+        )?;
+
+        self.attempts.push(attempt);
+        Ok(())
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Posts answers to the website, checking and updating the history on the way.
+pub struct Submitter {
+    site: Site,
+    history: History,
+}
+
+impl Submitter {
+    pub fn new(site: Site, history: History) -> Submitter {
+        Submitter { site, history }
+    }
+
+    #[instrument(skip(self, answer), fields(%answer))]
+    pub fn submit(&mut self, day: u32, part: Part, answer: &Answer) -> Result<Verdict> {
+        let answer = answer.to_string();
+        self.history.check(day, part, &answer, now())?;
+
+        let cookie = self.site.cookie("submit answers")?;
+        let url = self.site.url(&format!("day/{}/answer", day));
+        let level = match part {
+            Part::A => "1",
+            Part::B => "2",
+        };
+        info!(url, "Submitting answer");
+
+        let body = self
+            .site
+            .client()?
+            .post(&url)
+            .header(header::COOKIE, cookie)
+            .form(&[("level", level), ("answer", answer.as_str())])
+            .send()
+            .with_context(|| format!("Couldn't post to {}", url))?
+            .error_for_status()
+            .with_context(|| format!("Couldn't post to {}", url))?
+            .text()
+            .with_context(|| format!("Couldn't read response from {}", url))?;
+
+        let verdict = Verdict::parse(&body)?;
+        if matches!(verdict, Verdict::AlreadySolved) {
+            warn!("Part was already solved, the answer wasn't checked");
+        }
+
+        self.history.record(Attempt {
+            time: now(),
+            day,
+            part,
+            answer,
+            verdict,
+        })?;
+
+        Ok(verdict)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+    use crate::mock_http::MockServer;
+
+    fn history(name: &str) -> History {
+        let path = env::temp_dir().join(format!("advent24-{}-{}.tsv", name, process::id()));
+        let _ = fs::remove_file(&path);
+        History::load(path).unwrap()
+    }
+
+    #[test]
+    fn parses_responses() {
+        let cases = [
+            (
+                "<p>That's the right answer! You are one gold star closer",
+                Verdict::Correct,
+            ),
+            (
+                "That's not the right answer; your answer is too high.",
+                Verdict::TooHigh,
+            ),
+            (
+                "That's not the right answer; your answer is too low.",
+                Verdict::TooLow,
+            ),
+            (
+                "That's not the right answer. If you're stuck",
+                Verdict::Wrong,
+            ),
+            (
+                "You gave an answer too recently; you have 1m 5s left to wait.",
+                Verdict::Wait(65),
+            ),
+            (
+                "You gave an answer too recently; you have 38s left to wait.",
+                Verdict::Wait(38),
+            ),
+            (
+                "You don't seem to be solving the right level.  Did you already complete it?",
+                Verdict::AlreadySolved,
+            ),
+        ];
+        for (body, verdict) in cases {
+            assert_eq!(Verdict::parse(body).unwrap(), verdict, "{}", body);
+        }
+        assert!(Verdict::parse("<html>Something else</html>").is_err());
+    }
+
+    #[test]
+    fn refuses_known_bad_answers() {
+        let mut history = history("refuse");
+        for (answer, verdict) in [
+            ("100", Verdict::TooHigh),
+            ("10", Verdict::TooLow),
+            ("50", Verdict::Wrong),
+        ] {
+            history
+                .record(Attempt {
+                    time: 0,
+                    day: 1,
+                    part: Part::A,
+                    answer: answer.to_string(),
+                    verdict,
+                })
+                .unwrap();
+        }
+
+        for bad in ["100", "150", "10", "3", "50"] {
+            assert!(history.check(1, Part::A, bad, 1000).is_err(), "{}", bad);
+        }
+        assert!(history.check(1, Part::A, "42", 30).is_err());
+        assert!(history.check(1, Part::A, "42", 1000).is_ok());
+        assert!(history.check(1, Part::B, "150", 1000).is_ok());
+
+        // The history survives a reload.
+        let reloaded = History::load(history.path.clone()).unwrap();
+        assert_eq!(reloaded.attempts(1, Part::A).count(), 3);
+        assert!(reloaded.check(1, Part::A, "150", 1000).is_err());
+
+        fs::remove_file(&history.path).unwrap();
+    }
+
+    #[test]
+    fn submits_and_records() {
+        let server = MockServer::start(vec![(
+            200,
+            "<article><p>That's not the right answer; your answer is too low.</p></article>",
+        )]);
+        let site = Site {
+            base_url: server.url.clone(),
+            session: Some("secret".to_string()),
+        };
+        let mut history = history("submit");
+        let path = history.path.clone();
+        history
+            .record(Attempt {
+                time: 0,
+                day: 7,
+                part: Part::B,
+                answer: "500".to_string(),
+                verdict: Verdict::TooHigh,
+            })
+            .unwrap();
+        let mut submitter = Submitter::new(site, history);
+
+        // Refused locally, never reaches the server.
+        assert!(submitter.submit(7, Part::B, &Answer::Number(600)).is_err());
+        assert_eq!(
+            submitter.submit(7, Part::B, &Answer::Number(400)).unwrap(),
+            Verdict::TooLow
+        );
+        // Throttled by the wrong answer that was just given.
+        assert!(submitter.submit(7, Part::B, &Answer::Number(450)).is_err());
+
+        let requests = server.finish();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/day/7/answer");
+        assert_eq!(requests[0].body, "level=2&answer=400");
+        assert_eq!(requests[0].header("cookie"), Some("session=secret"));
+
+        let recorded = fs::read_to_string(&path).unwrap();
+        assert_eq!(recorded.lines().count(), 2);
+        assert!(
+            recorded
+                .lines()
+                .last()
+                .unwrap()
+                .ends_with("\t7\tb\t400\tlow")
+        );
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,338 @@
+//! The robots of day 14, each walking in a straight line across an area that wraps at the edges.
+//!
+//! A robot's row only depends on the time modulo the number of rows, and its column on the time
+//! modulo the number of columns, so any moment can be jumped to directly and the whole swarm
+//! repeats with a period of `rows * cols` when the two are coprime.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result, bail};
+use tracing::debug;
+
+use crate::util::{Grid, Position, crt, extended_gcd};
+
+/// Side of the square blocks robots are counted in to measure entropy.
+const ENTROPY_BLOCK: i32 = 5;
+
+pub struct Robot {
+    pub position: Position,
+    pub velocity: (i32, i32),
+}
+
+/// Measures of how structured a frame is. A picture packs robots together, so it has a low
+/// variance and entropy and a large cluster, and is usually drawn with no robots on top of each
+/// other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameScore {
+    pub time: i64,
+    pub row_variance: f64,
+    pub col_variance: f64,
+    /// Robots in the largest group of side by side robots.
+    pub largest_cluster: usize,
+    /// Shannon entropy, in bits, of the robots counted in `ENTROPY_BLOCK` squares.
+    pub entropy: f64,
+    /// Robots sharing a cell with an earlier one.
+    pub overlaps: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    Variance,
+    Cluster,
+    Entropy,
+    NoOverlap,
+}
+
+/// How part B picks the picture frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PictureSearch {
+    /// Best row time and best column time by variance, combined with the Chinese remainder
+    /// theorem.
+    Crt,
+    /// Best frame of a whole period by one signal.
+    Rank(Signal),
+}
+
+impl PictureSearch {
+    pub fn parse(name: &str) -> Result<PictureSearch> {
+        Ok(match name {
+            "crt" => PictureSearch::Crt,
+            "variance" => PictureSearch::Rank(Signal::Variance),
+            "cluster" => PictureSearch::Rank(Signal::Cluster),
+            "entropy" => PictureSearch::Rank(Signal::Entropy),
+            "no-overlap" => PictureSearch::Rank(Signal::NoOverlap),
+            _ => bail!(
+                "Unknown picture search {}, expected crt, variance, cluster, entropy or no-overlap",
+                name
+            ),
+        })
+    }
+}
+
+fn variance(values: impl Iterator<Item = i32>) -> f64 {
+    let values: Vec<f64> = values.map(f64::from).collect();
+    let n = values.len().max(1) as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / n
+}
+
+fn largest_cluster(occupied: &HashSet<Position>) -> usize {
+    let mut seen: HashSet<Position> = HashSet::new();
+    let mut largest = 0;
+    for start in occupied {
+        if !seen.insert(*start) {
+            continue;
+        }
+        let mut stack = vec![*start];
+        let mut size = 0;
+        while let Some((r, c)) = stack.pop() {
+            size += 1;
+            for next in [(r - 1, c), (r + 1, c), (r, c - 1), (r, c + 1)] {
+                if occupied.contains(&next) && seen.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+        largest = largest.max(size);
+    }
+    largest
+}
+
+fn entropy(positions: &[Position]) -> f64 {
+    let mut blocks: HashMap<Position, usize> = HashMap::new();
+    for (r, c) in positions {
+        *blocks
+            .entry((r / ENTROPY_BLOCK, c / ENTROPY_BLOCK))
+            .or_default() += 1;
+    }
+    let n = positions.len() as f64;
+    blocks
+        .values()
+        .map(|count| {
+            let p = *count as f64 / n;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+pub struct Swarm {
+    pub rows: i32,
+    pub cols: i32,
+    robots: Vec<Robot>,
+}
+
+impl Swarm {
+    /// Parse one `p=col,row v=col,row` robot per line.
+    pub fn parse(content: &str, rows: i32, cols: i32) -> Result<Swarm> {
+        if rows <= 0 || cols <= 0 {
+            bail!("The area must be at least 1x1, not {}x{}", rows, cols);
+        }
+
+        let pair = |s: &str, prefix: &str| -> Result<(i32, i32)> {
+            let (c, r) = s
+                .strip_prefix(prefix)
+                .and_then(|s| s.split_once(','))
+                .with_context(|| format!("Expected {}col,row in {:?}", prefix, s))?;
+            Ok((r.parse()?, c.parse()?))
+        };
+
+        let mut robots = Vec::new();
+        for (i, line) in content.lines().enumerate() {
+            let robot = || -> Result<Robot> {
+                let (p, v) = line
+                    .split_once(' ')
+                    .context("Expected a position and velocity")?;
+                Ok(Robot {
+                    position: pair(p, "p=")?,
+                    velocity: pair(v, "v=")?,
+                })
+            };
+            robots.push(robot().with_context(|| format!("Bad robot on line {}", i + 1))?);
+        }
+        debug!("Got {:?} Robots", robots.len());
+
+        Ok(Swarm { rows, cols, robots })
+    }
+
+    /// Steps after which every robot is back where it started.
+    pub fn period(&self) -> i64 {
+        let (g, _, _) = extended_gcd(self.rows as i64, self.cols as i64);
+        self.rows as i64 / g * self.cols as i64
+    }
+
+    fn row_at(&self, robot: &Robot, time: i64) -> i32 {
+        (robot.position.0 as i64 + time * robot.velocity.0 as i64).rem_euclid(self.rows as i64)
+            as i32
+    }
+
+    fn col_at(&self, robot: &Robot, time: i64) -> i32 {
+        (robot.position.1 as i64 + time * robot.velocity.1 as i64).rem_euclid(self.cols as i64)
+            as i32
+    }
+
+    /// Where every robot is after `time` steps, which may be negative.
+    pub fn at(&self, time: i64) -> Vec<Position> {
+        self.robots
+            .iter()
+            .map(|r| (self.row_at(r, time), self.col_at(r, time)))
+            .collect()
+    }
+
+    /// Product of the robots in each quadrant, leaving out the middle row and column.
+    pub fn safety_factor(&self, time: i64) -> usize {
+        let (mid_r, mid_c) = (self.rows / 2, self.cols / 2);
+        let mut quadrants = [0; 4];
+        for (r, c) in self.at(time) {
+            if r == mid_r || c == mid_c {
+                continue;
+            }
+            quadrants[(r > mid_r) as usize * 2 + (c > mid_c) as usize] += 1;
+        }
+        debug!("Quadrants: {:?}", quadrants);
+        quadrants.iter().product()
+    }
+
+    pub fn frame(&self, time: i64) -> Grid<bool> {
+        let mut grid = Grid::new(self.rows, self.cols, false);
+        for pos in self.at(time) {
+            grid[pos] = true;
+        }
+        grid
+    }
+
+    pub fn score(&self, time: i64) -> FrameScore {
+        let positions = self.at(time);
+        let occupied: HashSet<Position> = positions.iter().copied().collect();
+        FrameScore {
+            time,
+            row_variance: variance(positions.iter().map(|p| p.0)),
+            col_variance: variance(positions.iter().map(|p| p.1)),
+            largest_cluster: largest_cluster(&occupied),
+            entropy: entropy(&positions),
+            overlaps: positions.len() - occupied.len(),
+        }
+    }
+
+    /// Every frame of one period, most picture-like by `signal` first. Ties go to the earliest.
+    pub fn rank(&self, signal: Signal) -> Vec<FrameScore> {
+        let mut scores: Vec<FrameScore> = (0..self.period()).map(|t| self.score(t)).collect();
+        scores.sort_by(|a, b| {
+            let order = match signal {
+                Signal::Variance => {
+                    (a.row_variance + a.col_variance).total_cmp(&(b.row_variance + b.col_variance))
+                }
+                Signal::Cluster => b.largest_cluster.cmp(&a.largest_cluster),
+                Signal::Entropy => a.entropy.total_cmp(&b.entropy),
+                Signal::NoOverlap => a.overlaps.cmp(&b.overlaps),
+            };
+            order.then(a.time.cmp(&b.time))
+        });
+        scores
+    }
+
+    /// The time rows alone, or columns alone, are least spread out. It repeats every `modulus`.
+    fn tightest(&self, modulus: i32, axis: impl Fn(&Robot, i64) -> i32) -> i64 {
+        (0..modulus as i64)
+            .min_by(|a, b| {
+                let va = variance(self.robots.iter().map(|r| axis(r, *a)));
+                let vb = variance(self.robots.iter().map(|r| axis(r, *b)));
+                va.total_cmp(&vb)
+            })
+            .unwrap_or(0)
+    }
+
+    /// The first time the robots are likely to draw a picture.
+    pub fn picture(&self, search: PictureSearch) -> Result<i64> {
+        let time = match search {
+            PictureSearch::Crt => {
+                let row_time = self.tightest(self.rows, |r, t| self.row_at(r, t));
+                let col_time = self.tightest(self.cols, |r, t| self.col_at(r, t));
+                debug!(row_time, col_time, "Tightest rows and columns");
+                let (time, _) = crt(row_time, self.rows as i64, col_time, self.cols as i64)
+                    .with_context(|| {
+                        format!(
+                            "Rows are tightest at {} mod {} and columns at {} mod {}, which never \
+                         happen together",
+                            row_time, self.rows, col_time, self.cols
+                        )
+                    })?;
+                time
+            }
+            PictureSearch::Rank(signal) => {
+                let ranked = self.rank(signal);
+                for score in ranked.iter().take(5) {
+                    debug!(?score, "Top frame");
+                }
+                ranked.first().context("No robots")?.time
+            }
+        };
+
+        debug!(
+            "Step {}\n{}",
+            time,
+            self.frame(time).map(|b| if *b { '#' } else { '.' })
+        );
+        Ok(time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "p=0,4 v=3,-3\np=6,3 v=-1,-3\np=10,3 v=-1,2\np=2,0 v=2,-1\n\
+                           p=0,0 v=1,3\np=3,0 v=-2,-2\np=7,6 v=-1,-3\np=3,0 v=-1,-2\n\
+                           p=9,3 v=2,3\np=7,3 v=-1,2\np=2,4 v=2,-3\np=9,5 v=-3,-3";
+
+    /// A 5x5 block of robots that scatter from time `at`, each with its own velocity.
+    fn block_swarm(rows: i32, cols: i32, at: i64) -> Swarm {
+        let mut robots = Vec::new();
+        for i in 0..25 {
+            let velocity = (1 + (i * 7) % (rows - 1), 1 + (i * 11) % (cols - 1));
+            let target = (10 + i / 5, 20 + i % 5);
+            robots.push(Robot {
+                position: target,
+                velocity,
+            });
+        }
+        let swarm = Swarm { rows, cols, robots };
+        let start = swarm.at(-at);
+        Swarm {
+            robots: swarm
+                .robots
+                .into_iter()
+                .zip(start)
+                .map(|(r, position)| Robot { position, ..r })
+                .collect(),
+            ..swarm
+        }
+    }
+
+    #[test]
+    fn jumps_to_any_time() {
+        let swarm = Swarm::parse(EXAMPLE, 7, 11).unwrap();
+        assert_eq!(swarm.period(), 77);
+        assert_eq!(swarm.safety_factor(100), 12);
+        assert_eq!(swarm.at(5)[10], (3, 1));
+        assert_eq!(swarm.at(5), swarm.at(5 + 77 * 1000));
+
+        assert!(Swarm::parse("p=1,2 v=3", 7, 11).is_err());
+    }
+
+    #[test]
+    fn finds_pictures() {
+        let swarm = block_swarm(31, 37, 500);
+        assert_eq!(swarm.score(500).largest_cluster, 25);
+        assert_eq!(swarm.picture(PictureSearch::Crt).unwrap(), 500);
+        for signal in [Signal::Variance, Signal::Cluster, Signal::Entropy] {
+            assert_eq!(
+                swarm.picture(PictureSearch::Rank(signal)).unwrap(),
+                500,
+                "{:?}",
+                signal
+            );
+        }
+        assert_eq!(swarm.rank(Signal::NoOverlap)[0].overlaps, 0);
+        assert!(PictureSearch::parse("tree").is_err());
+    }
+}
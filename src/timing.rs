@@ -0,0 +1,185 @@
+use std::{
+    any::Any,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use tracing::warn;
+
+use crate::solution::{Part, Solver};
+
+/// How long one day took to parse and solve each part. A part that failed has no time.
+pub struct DayTiming {
+    pub day: u32,
+    pub parse: Duration,
+    pub part_a: Option<Duration>,
+    pub part_b: Option<Duration>,
+}
+
+impl DayTiming {
+    pub fn total(&self) -> Duration {
+        self.parse + self.part_a.unwrap_or_default() + self.part_b.unwrap_or_default()
+    }
+}
+
+fn timed<T>(f: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = f();
+    (result, start.elapsed())
+}
+
+fn solve_timed(solver: &dyn Solver, input: &dyn Any, part: Part) -> Result<Duration> {
+    let (answer, elapsed) = timed(|| solver.solve_any(input, part));
+    answer?;
+    Ok(elapsed)
+}
+
+/// Parse once and solve both parts, timing each step separately.
+pub fn time_day(day: u32, solver: &dyn Solver, content: &str) -> Result<DayTiming> {
+    let (input, parse) = timed(|| solver.parse_any(content));
+    let input = input?;
+
+    let [part_a, part_b] = [Part::A, Part::B].map(|part| {
+        solve_timed(solver, input.as_ref(), part)
+            .inspect_err(|e| warn!(day, %part, "Failed: {:#}", e))
+            .ok()
+    });
+
+    Ok(DayTiming {
+        day,
+        parse,
+        part_a,
+        part_b,
+    })
+}
+
+fn show(d: Option<Duration>) -> String {
+    match d {
+        Some(d) => format!("{:.2?}", d),
+        None => "-".to_string(),
+    }
+}
+
+/// Table of the timings for several days, with a total row.
+pub struct Summary(pub Vec<DayTiming>);
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>4} {:>12} {:>12} {:>12} {:>12}",
+            "Day", "Parse", "Part A", "Part B", "Total"
+        )?;
+        for t in &self.0 {
+            writeln!(
+                f,
+                "{:>4} {:>12} {:>12} {:>12} {:>12}",
+                t.day,
+                show(Some(t.parse)),
+                show(t.part_a),
+                show(t.part_b),
+                show(Some(t.total()))
+            )?;
+        }
+        let total: Duration = self.0.iter().map(|t| t.total()).sum();
+        write!(f, "{:>4} {:>51}", "All", show(Some(total)))
+    }
+}
+
+/// Min, median and max of repeated runs of one step.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(mut samples: Vec<Duration>) -> Stats {
+        assert!(!samples.is_empty(), "Need at least one sample");
+        samples.sort();
+        let n = samples.len();
+        let median = if n % 2 == 1 {
+            samples[n / 2]
+        } else {
+            (samples[n / 2 - 1] + samples[n / 2]) / 2
+        };
+        Stats {
+            min: samples[0],
+            median,
+            max: samples[n - 1],
+        }
+    }
+}
+
+/// Bench results for the parse and both parts. A part that failed is left out.
+pub struct Bench {
+    pub repeats: usize,
+    pub steps: Vec<(&'static str, Stats)>,
+}
+
+impl fmt::Display for Bench {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} repeats", self.repeats)?;
+        write!(f, "{:>8} {:>12} {:>12} {:>12}", "", "Min", "Median", "Max")?;
+        for (name, s) in &self.steps {
+            write!(
+                f,
+                "\n{:>8} {:>12} {:>12} {:>12}",
+                name,
+                show(Some(s.min)),
+                show(Some(s.median)),
+                show(Some(s.max))
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Repeat the parse and each part `repeats` times.
+pub fn bench(solver: &dyn Solver, content: &str, repeats: usize) -> Result<Bench> {
+    let mut parse = Vec::with_capacity(repeats);
+    let mut input = None;
+    for _ in 0..repeats {
+        let (parsed, elapsed) = timed(|| solver.parse_any(content));
+        parse.push(elapsed);
+        input = Some(parsed?);
+    }
+    let input = input.expect("At least one repeat");
+
+    let mut steps = vec![("Parse", Stats::from_samples(parse))];
+    for (name, part) in [("Part A", Part::A), ("Part B", Part::B)] {
+        let samples: Result<Vec<Duration>> = (0..repeats)
+            .map(|_| solve_timed(solver, input.as_ref(), part))
+            .collect();
+        match samples {
+            Ok(samples) => steps.push((name, Stats::from_samples(samples))),
+            Err(e) => warn!(%part, "Failed: {:#}", e),
+        }
+    }
+
+    Ok(Bench { repeats, steps })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_of_samples() {
+        let ms = Duration::from_millis;
+        assert_eq!(
+            Stats::from_samples(vec![ms(5), ms(1), ms(3)]),
+            Stats {
+                min: ms(1),
+                median: ms(3),
+                max: ms(5)
+            }
+        );
+        assert_eq!(
+            Stats::from_samples(vec![ms(4), ms(1), ms(2), ms(9)]).median,
+            ms(3)
+        );
+    }
+}
@@ -1,77 +1,127 @@
-use std::collections::{HashMap, HashSet};
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap, HashSet},
+    fmt,
+    hash::Hash,
+    ops::{Index, IndexMut},
+};
 
+use anyhow::{Result, bail};
 use tracing::instrument;
 
+/// A `(row, col)` position, rows growing downwards.
+pub type Position = (i32, i32);
+
+/// Compass directions, in clockwise order from North. North is towards row 0.
 #[derive(Debug, Hash, Eq, PartialEq, Clone)]
 pub enum Direction {
     North,
+    NorthEast,
     East,
-    West,
+    SouthEast,
     South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    /// All eight directions, clockwise from North.
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    /// The four directions along the grid, clockwise from North.
+    pub const CARDINAL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    /// Number of 45 degree steps clockwise from North.
+    fn eighths(&self) -> i32 {
+        Direction::ALL.iter().position(|d| d == self).unwrap() as i32
+    }
+
+    /// The `(row, col)` change from moving one step this way.
+    pub fn offset(&self) -> (i32, i32) {
+        match self {
+            Direction::North => (-1, 0),
+            Direction::NorthEast => (-1, 1),
+            Direction::East => (0, 1),
+            Direction::SouthEast => (1, 1),
+            Direction::South => (1, 0),
+            Direction::SouthWest => (1, -1),
+            Direction::West => (0, -1),
+            Direction::NorthWest => (-1, -1),
+        }
+    }
+
+    /// The `^`, `>`, `v` or `<` arrow for a cardinal direction.
+    pub fn arrow(&self) -> Option<char> {
+        match self {
+            Direction::North => Some('^'),
+            Direction::East => Some('>'),
+            Direction::South => Some('v'),
+            Direction::West => Some('<'),
+            _ => None,
+        }
+    }
+
+    pub fn from_arrow(c: char) -> Option<Direction> {
+        match c {
+            '^' => Some(Direction::North),
+            '>' => Some(Direction::East),
+            'v' => Some(Direction::South),
+            '<' => Some(Direction::West),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum Rotation {
+    /// 90 degrees anticlockwise
     Left,
+    /// 90 degrees clockwise
     Right,
+    /// 45 degrees anticlockwise
+    HalfLeft,
+    /// 45 degrees clockwise
+    HalfRight,
+    /// 180 degrees
+    Reverse,
 }
 
 #[instrument]
 pub fn opposite_direction(direction: &Direction) -> Direction {
-    match direction {
-        Direction::North => Direction::South,
-        Direction::East => Direction::West,
-        Direction::West => Direction::East,
-        Direction::South => Direction::North,
-    }
+    turn(direction, Rotation::Reverse)
 }
 
 #[instrument]
 pub fn move_direction(start: &(i32, i32), direction: &Direction) -> (i32, i32) {
-    match direction {
-        Direction::North => (start.0 - 1, start.1),
-        Direction::East => (start.0, start.1 + 1),
-        Direction::West => (start.0, start.1 - 1),
-        Direction::South => (start.0 + 1, start.1),
-    }
+    let (dr, dc) = direction.offset();
+    (start.0 + dr, start.1 + dc)
 }
 
 #[instrument]
 pub fn turn(direction: &Direction, rotation: Rotation) -> Direction {
-    match direction {
-        Direction::North => match rotation {
-            Rotation::Left => {
-                return Direction::West;
-            }
-            Rotation::Right => {
-                return Direction::East;
-            }
-        },
-        Direction::East => match rotation {
-            Rotation::Left => {
-                return Direction::North;
-            }
-            Rotation::Right => {
-                return Direction::South;
-            }
-        },
-        Direction::West => match rotation {
-            Rotation::Left => {
-                return Direction::South;
-            }
-            Rotation::Right => {
-                return Direction::North;
-            }
-        },
-        Direction::South => match rotation {
-            Rotation::Left => {
-                return Direction::East;
-            }
-            Rotation::Right => {
-                return Direction::West;
-            }
-        },
-    }
+    let eighths = match rotation {
+        Rotation::Left => -2,
+        Rotation::Right => 2,
+        Rotation::HalfLeft => -1,
+        Rotation::HalfRight => 1,
+        Rotation::Reverse => 4,
+    };
+    Direction::ALL[(direction.eighths() + eighths).rem_euclid(8) as usize].clone()
 }
 
 #[instrument]
@@ -87,55 +137,522 @@ pub fn is_in_bounds(rows: i32, cols: i32, row: i32, col: i32) -> bool {
     true
 }
 
+/// `(g, x, y)` with `g = gcd(a, b)` and `a * x + b * y = g`.
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    if b == 0 {
+        return if a < 0 { (-a, -1, 0) } else { (a, 1, 0) };
+    }
+    let (g, x, y) = extended_gcd(b, a.rem_euclid(b));
+    (g, y, x - a.div_euclid(b) * y)
+}
+
+/// The smallest `t >= 0` with `t = a1 (mod m1)` and `t = a2 (mod m2)`, along with `lcm(m1, m2)`,
+/// the period of every other solution. `None` if the two can't both hold.
+pub fn crt(a1: i64, m1: i64, a2: i64, m2: i64) -> Option<(i64, i64)> {
+    let (g, x, _) = extended_gcd(m1, m2);
+    if (a2 - a1) % g != 0 {
+        return None;
+    }
+    let lcm = m1 / g * m2;
+    let m = (m2 / g) as i128;
+    let k = (((a2 - a1) / g) as i128 % m * x as i128).rem_euclid(m);
+    let t = (a1 as i128 + m1 as i128 * k).rem_euclid(lcm as i128);
+    Some((t as i64, lcm))
+}
+
+/// What `shortest_paths` should work out on top of the distances.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PathOptions {
+    /// Reconstruct one shortest route from start to end.
+    pub path: bool,
+    /// Collect every cell on any shortest route from start to end.
+    pub cells: bool,
+}
+
+pub struct ShortestPaths {
+    pub distances: HashMap<Position, i32>,
+    /// One shortest route, start and end included, if asked for and the end was reached.
+    pub path: Option<Vec<Position>>,
+    /// Every cell on any shortest route, if asked for. Empty when the end wasn't reached.
+    pub cells: Option<HashSet<Position>>,
+}
+
+/// Steps from `start` to every reachable open cell of `blockers`, stopping early once `end` is
+/// reached when `shortcut` is set.
 pub fn shortest_distance(
-    start: (i32, i32),
-    end: (i32, i32),
-    blockers: &HashSet<(i32, i32)>,
-    rows: i32,
-    cols: i32,
+    start: Position,
+    end: Position,
+    blockers: &Grid<bool>,
     shortcut: bool,
-    max_len: Option<i32>,
-) -> HashMap<(i32, i32), i32> {
-    let mut scores: HashMap<(i32, i32), i32> = HashMap::new();
-    let mut front: Vec<((i32, i32), i32)> = Vec::new();
+) -> HashMap<Position, i32> {
+    shortest_paths(start, end, blockers, shortcut, PathOptions::default()).distances
+}
+
+/// `shortest_distance`, optionally also returning the routes to `end`. The routes come from the
+/// predecessors recorded during the search.
+pub fn shortest_paths(
+    start: Position,
+    end: Position,
+    blockers: &Grid<bool>,
+    shortcut: bool,
+    options: PathOptions,
+) -> ShortestPaths {
+    let neighbors = |pos: &Position| {
+        blockers
+            .neighbors(*pos)
+            .filter(|(_, next)| !blockers[*next])
+            .map(|(_, next)| (next, 1))
+            .collect::<Vec<_>>()
+    };
+
+    let search = if shortcut {
+        let distance = |pos: &Position| (pos.0 - end.0).abs() + (pos.1 - end.1).abs();
+        astar([start], neighbors, distance, |pos| *pos == end)
+    } else {
+        dijkstra([start], neighbors, |_| false)
+    };
+
+    ShortestPaths {
+        path: options.path.then(|| search.path_to(&end)).flatten(),
+        cells: options.cells.then(|| search.on_any_path([end])),
+        distances: search.distances,
+    }
+}
+
+/// Everything found by a `dijkstra` or `astar` search.
+pub struct Search<S> {
+    /// Lowest cost to every state that was reached.
+    pub distances: HashMap<S, i32>,
+    /// For each state, every state it can be reached from on a lowest cost route.
+    pub predecessors: HashMap<S, Vec<S>>,
+    /// The first goal state reached, if any.
+    pub goal: Option<S>,
+}
+
+impl<S: Hash + Eq + Clone> Search<S> {
+    pub fn distance(&self, state: &S) -> Option<i32> {
+        self.distances.get(state).copied()
+    }
 
-    scores.insert(start, 0);
-    front.push((start, 0));
+    /// One lowest cost route from a start to `end`, inclusive of both.
+    pub fn path_to(&self, end: &S) -> Option<Vec<S>> {
+        self.distances.get(end)?;
 
-    while let Some((pos, score)) = front.pop() {
-        if (pos == end) && shortcut {
-            scores.insert(pos, score);
-            return scores;
+        let mut path = vec![end.clone()];
+        while let Some(previous) = self.predecessors.get(path.last().unwrap()) {
+            path.push(previous[0].clone());
         }
+        path.reverse();
+
+        Some(path)
+    }
 
-        if let Some(x) = max_len {
-            if score > x {
+    /// Every state on any lowest cost route to any of `ends`.
+    pub fn on_any_path(&self, ends: impl IntoIterator<Item = S>) -> HashSet<S> {
+        let mut seen: HashSet<S> = HashSet::new();
+        let mut to_visit: Vec<S> = ends
+            .into_iter()
+            .filter(|e| self.distances.contains_key(e))
+            .collect();
+
+        while let Some(state) = to_visit.pop() {
+            if !seen.insert(state.clone()) {
                 continue;
             }
+            if let Some(previous) = self.predecessors.get(&state) {
+                to_visit.extend(previous.iter().cloned());
+            }
         }
 
-        for d in [
-            Direction::North,
-            Direction::East,
-            Direction::South,
-            Direction::West,
-        ] {
-            let test_pos = move_direction(&pos, &d);
+        seen
+    }
+}
 
-            if !is_in_bounds(rows, cols, test_pos.0, test_pos.1) {
-                continue;
-            }
+/// Heap entry ordered so the `BinaryHeap` pops the lowest estimated total first.
+struct Entry<S> {
+    estimate: i32,
+    cost: i32,
+    state: S,
+}
 
-            if !blockers.contains(&test_pos) {
-                let current_score = scores.get(&test_pos).unwrap_or(&(score + 2)).clone();
-                if score + 1 < current_score {
-                    scores.insert(test_pos, score + 1);
-                    front.push((test_pos, score + 1));
+impl<S> PartialEq for Entry<S> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.estimate, self.cost) == (other.estimate, other.cost)
+    }
+}
+
+impl<S> Eq for Entry<S> {}
+
+impl<S> PartialOrd for Entry<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S> Ord for Entry<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed for a min-heap, preferring the deepest entry on a tie.
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then_with(|| self.cost.cmp(&other.cost))
+    }
+}
+
+/// Lowest cost search from `starts`, see `astar`.
+pub fn dijkstra<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    neighbors: impl FnMut(&S) -> I,
+    is_goal: impl Fn(&S) -> bool,
+) -> Search<S>
+where
+    S: Hash + Eq + Clone,
+    I: IntoIterator<Item = (S, i32)>,
+{
+    astar(starts, neighbors, |_| 0, is_goal)
+}
+
+/// A* search from `starts`. `neighbors` gives the states reachable from a state and the cost of
+/// each move, and `heuristic` must never overestimate the remaining cost to a goal.
+///
+/// The search stops once every route costing no more than the cheapest goal has been explored,
+/// so the predecessors cover all the best routes to the goal. Use `|_| false` as the goal to
+/// explore everything reachable.
+pub fn astar<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut neighbors: impl FnMut(&S) -> I,
+    heuristic: impl Fn(&S) -> i32,
+    is_goal: impl Fn(&S) -> bool,
+) -> Search<S>
+where
+    S: Hash + Eq + Clone,
+    I: IntoIterator<Item = (S, i32)>,
+{
+    let mut distances: HashMap<S, i32> = HashMap::new();
+    let mut predecessors: HashMap<S, Vec<S>> = HashMap::new();
+    let mut heap: BinaryHeap<Entry<S>> = BinaryHeap::new();
+    let mut goal: Option<(S, i32)> = None;
+
+    for start in starts {
+        distances.insert(start.clone(), 0);
+        heap.push(Entry {
+            estimate: heuristic(&start),
+            cost: 0,
+            state: start,
+        });
+    }
+
+    while let Some(Entry {
+        estimate,
+        cost,
+        state,
+    }) = heap.pop()
+    {
+        if let Some((_, best)) = &goal
+            && estimate > *best
+        {
+            break;
+        }
+        if cost > distances[&state] {
+            // Already found a cheaper way here
+            continue;
+        }
+        if goal.is_none() && is_goal(&state) {
+            goal = Some((state.clone(), cost));
+        }
+
+        for (next, step) in neighbors(&state) {
+            let next_cost = cost + step;
+            match distances.get(&next) {
+                Some(&d) if next_cost > d => {}
+                Some(&d) if next_cost == d => {
+                    predecessors.entry(next).or_default().push(state.clone());
+                }
+                _ => {
+                    distances.insert(next.clone(), next_cost);
+                    predecessors.insert(next.clone(), vec![state.clone()]);
+                    heap.push(Entry {
+                        estimate: next_cost + heuristic(&next),
+                        cost: next_cost,
+                        state: next,
+                    });
                 }
             }
         }
-        front.sort_by_key(|x| -x.1);
     }
 
-    return scores;
+    Search {
+        distances,
+        predecessors,
+        goal: goal.map(|(s, _)| s),
+    }
+}
+
+/// A dense, rectangular grid of cells addressed by `(row, col)`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    rows: i32,
+    cols: i32,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// A `rows` by `cols` grid with every cell set to `fill`.
+    pub fn new(rows: i32, cols: i32, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            rows,
+            cols,
+            cells: vec![fill; (rows * cols) as usize],
+        }
+    }
+
+    /// Build a grid from its rows, which must all be the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Grid<T>> {
+        let cols = rows.first().map_or(0, |r| r.len());
+        if let Some((i, _)) = rows.iter().enumerate().find(|(_, r)| r.len() != cols) {
+            bail!("Row {} has {} cells, expected {}", i, rows[i].len(), cols);
+        }
+
+        Ok(Grid {
+            rows: rows.len() as i32,
+            cols: cols as i32,
+            cells: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Parse one row per line, converting each character with `cell`. Stops at the first blank
+    /// line so a grid can be followed by other sections.
+    pub fn parse(content: &str, mut cell: impl FnMut(char) -> Result<T>) -> Result<Grid<T>> {
+        let rows = content
+            .lines()
+            .take_while(|line| !line.is_empty())
+            .map(|line| line.chars().map(&mut cell).collect::<Result<Vec<T>>>())
+            .collect::<Result<Vec<_>>>()?;
+        Grid::from_rows(rows)
+    }
+
+    pub fn rows(&self) -> i32 {
+        self.rows
+    }
+
+    pub fn cols(&self) -> i32 {
+        self.cols
+    }
+
+    pub fn in_bounds(&self, pos: Position) -> bool {
+        is_in_bounds(self.rows, self.cols, pos.0, pos.1)
+    }
+
+    fn offset(&self, pos: Position) -> Option<usize> {
+        self.in_bounds(pos)
+            .then(|| (pos.0 * self.cols + pos.1) as usize)
+    }
+
+    pub fn get(&self, pos: Position) -> Option<&T> {
+        self.offset(pos).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, pos: Position) -> Option<&mut T> {
+        self.offset(pos).map(|i| &mut self.cells[i])
+    }
+
+    /// Every position, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = Position> + use<T> {
+        let cols = self.cols;
+        (0..self.rows).flat_map(move |r| (0..cols).map(move |c| (r, c)))
+    }
+
+    /// Every cell with its position, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// The in bounds positions next to `pos` and the direction to get to each of them.
+    pub fn neighbors(&self, pos: Position) -> impl Iterator<Item = (Direction, Position)> + '_ {
+        Direction::CARDINAL
+            .into_iter()
+            .map(move |d| {
+                let next = move_direction(&pos, &d);
+                (d, next)
+            })
+            .filter(|(_, next)| self.in_bounds(*next))
+    }
+
+    /// Position of the first cell, row by row, equal to `value`.
+    pub fn find(&self, value: &T) -> Option<Position>
+    where
+        T: PartialEq,
+    {
+        self.iter().find(|(_, x)| *x == value).map(|(p, _)| p)
+    }
+
+    /// Positions of every cell equal to `value`.
+    pub fn find_all<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Position> + 'a
+    where
+        T: PartialEq,
+    {
+        self.iter()
+            .filter(move |(_, x)| *x == value)
+            .map(|(p, _)| p)
+    }
+
+    pub fn row(&self, row: i32) -> &[T] {
+        let start = (row * self.cols) as usize;
+        &self.cells[start..start + self.cols as usize]
+    }
+
+    pub fn col(&self, col: i32) -> impl Iterator<Item = &T> {
+        (0..self.rows).map(move |r| &self[(r, col)])
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.cols.max(1) as usize)
+    }
+
+    /// A grid of the same shape with `f` applied to every cell.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            rows: self.rows,
+            cols: self.cols,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl Grid<char> {
+    pub fn parse_chars(content: &str) -> Result<Grid<char>> {
+        Grid::parse(content, Ok)
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Position) -> &T {
+        match self.get(pos) {
+            Some(x) => x,
+            None => panic!("{:?} is outside a {}x{} grid", pos, self.rows, self.cols),
+        }
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, pos: Position) -> &mut T {
+        let (rows, cols) = (self.rows, self.cols);
+        match self.get_mut(pos) {
+            Some(x) => x,
+            None => panic!("{:?} is outside a {}x{} grid", pos, rows, cols),
+        }
+    }
+}
+
+/// One line per row, each cell using its own `Display`.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, row) in self.iter_rows().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solves_congruences() {
+        assert_eq!(extended_gcd(240, 46), (2, -9, 47));
+        assert_eq!(crt(2, 3, 3, 5), Some((8, 15)));
+        assert_eq!(crt(1, 4, 3, 6), Some((9, 12)));
+        assert_eq!(crt(1, 4, 2, 6), None);
+    }
+
+    #[test]
+    fn grid_parse_and_access() {
+        let grid = Grid::parse_chars("#.S\n.E#\n\ntrailing section").unwrap();
+        assert_eq!((grid.rows(), grid.cols()), (2, 3));
+        assert_eq!(grid.find(&'E'), Some((1, 1)));
+        assert_eq!(grid.get((2, 0)), None);
+        assert_eq!(grid.get((0, -1)), None);
+        assert_eq!(grid.row(1), &['.', 'E', '#']);
+        assert_eq!(grid.col(2).collect::<String>(), "S#");
+        assert_eq!(grid.find_all(&'#').collect::<Vec<_>>(), [(0, 0), (1, 2)]);
+        assert_eq!(
+            grid.neighbors((0, 0)).collect::<Vec<_>>(),
+            [(Direction::East, (0, 1)), (Direction::South, (1, 0))]
+        );
+        assert_eq!(grid.to_string(), "#.S\n.E#");
+
+        assert!(Grid::parse_chars("##\n#").is_err());
+    }
+
+    #[test]
+    fn search_keeps_every_best_route() {
+        // Two equal routes from a to d, and a dearer direct edge.
+        let edges: HashMap<char, Vec<(char, i32)>> = HashMap::from([
+            ('a', vec![('b', 1), ('c', 2), ('d', 5)]),
+            ('b', vec![('d', 3)]),
+            ('c', vec![('d', 2)]),
+        ]);
+        let neighbors = |s: &char| edges.get(s).cloned().unwrap_or_default();
+
+        let search = dijkstra(['a'], neighbors, |s| *s == 'd');
+        assert_eq!(search.goal, Some('d'));
+        assert_eq!(search.distance(&'d'), Some(4));
+        assert_eq!(search.path_to(&'d').unwrap().len(), 3);
+        assert_eq!(
+            search.on_any_path(['d']),
+            HashSet::from(['a', 'b', 'c', 'd'])
+        );
+        assert_eq!(search.path_to(&'z'), None);
+
+        let walls = Grid::parse("....\n.##.\n....", |c| Ok(c == '#')).unwrap();
+        let options = PathOptions {
+            path: true,
+            cells: true,
+        };
+        let paths = shortest_paths((0, 0), (2, 3), &walls, false, options);
+        assert_eq!(paths.distances.get(&(0, 3)), Some(&3));
+        assert_eq!(paths.path.unwrap().len(), 6);
+        // Around either side of the wall, so every open cell
+        assert_eq!(paths.cells.unwrap().len(), 10);
+    }
+
+    #[test]
+    fn direction_turns_and_arrows() {
+        assert_eq!(turn(&Direction::North, Rotation::Left), Direction::West);
+        assert_eq!(turn(&Direction::West, Rotation::Right), Direction::North);
+        assert_eq!(
+            turn(&Direction::North, Rotation::HalfLeft),
+            Direction::NorthWest
+        );
+        assert_eq!(
+            turn(&Direction::SouthEast, Rotation::HalfRight),
+            Direction::South
+        );
+        assert_eq!(
+            opposite_direction(&Direction::NorthEast),
+            Direction::SouthWest
+        );
+        assert_eq!(move_direction(&(3, 3), &Direction::SouthWest), (4, 2));
+
+        for d in Direction::CARDINAL {
+            assert_eq!(Direction::from_arrow(d.arrow().unwrap()), Some(d));
+        }
+        assert!(
+            Direction::CARDINAL
+                .iter()
+                .all(|d| turn(d, Rotation::HalfRight).arrow().is_none())
+        );
+        assert_eq!(Direction::from_arrow('x'), None);
+    }
 }
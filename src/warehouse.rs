@@ -0,0 +1,347 @@
+//! The day 15 warehouse: a robot pushing boxes around, one instruction at a time, keeping a log
+//! of every move so it can be undone or replayed to any point.
+
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+};
+
+use anyhow::{Result, bail};
+use tracing::warn;
+
+use crate::util::{Direction, Grid, Position, move_direction, opposite_direction};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+    Wall,
+    Empty,
+    Box,
+    BoxLeft,
+    BoxRight,
+}
+
+impl fmt::Display for Tile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Tile::Wall => '#',
+            Tile::Empty => '.',
+            Tile::Box => 'O',
+            Tile::BoxLeft => '[',
+            Tile::BoxRight => ']',
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+/// One instruction as it played out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Move {
+    pub direction: Direction,
+    /// Where the robot was before the instruction.
+    pub robot: Position,
+    /// The left (or only) cell of every box pushed, before it moved.
+    pub boxes: Vec<Position>,
+    /// A wall stopped the robot, or a box it would have pushed.
+    pub blocked: bool,
+}
+
+/// The puzzle input: the warehouse layout and the robot's instructions.
+pub struct RobotPlan {
+    pub layout: Grid<char>,
+    pub directions: Vec<Direction>,
+}
+
+impl RobotPlan {
+    pub fn parse(content: &str) -> Result<RobotPlan> {
+        let layout = Grid::parse_chars(content)?;
+        let mut directions: Vec<Direction> = Vec::new();
+
+        for line in content.lines().skip_while(|line| !line.is_empty()) {
+            for c in line.chars() {
+                match Direction::from_arrow(c) {
+                    Some(d) => directions.push(d),
+                    None => warn!(char = ?c, "Found Bad Character"),
+                };
+            }
+        }
+
+        Ok(RobotPlan { layout, directions })
+    }
+}
+
+#[derive(Clone)]
+pub struct Warehouse {
+    map: Grid<Tile>,
+    robot: Position,
+    log: Vec<Move>,
+}
+
+impl Warehouse {
+    /// Lay out the warehouse, doubling the width of every tile when `wide` is set.
+    pub fn new(layout: &Grid<char>, wide: bool) -> Result<Warehouse> {
+        let width = if wide { 2 } else { 1 };
+        let mut map = Grid::new(layout.rows(), layout.cols() * width, Tile::Empty);
+        let mut robot = None;
+
+        for ((row, col), c) in layout.iter() {
+            let tiles: &[Tile] = match (c, wide) {
+                ('#', false) => &[Tile::Wall],
+                ('#', true) => &[Tile::Wall, Tile::Wall],
+                ('O', false) => &[Tile::Box],
+                ('O', true) => &[Tile::BoxLeft, Tile::BoxRight],
+                ('@', _) if robot.is_some() => bail!("More than one robot in the warehouse"),
+                ('@', _) => {
+                    robot = Some((row, col * width));
+                    &[]
+                }
+                ('.', _) => &[],
+                _ => bail!("Unexpected {:?} at {:?} in the warehouse", c, (row, col)),
+            };
+            for (i, tile) in tiles.iter().enumerate() {
+                map[(row, col * width + i as i32)] = *tile;
+            }
+        }
+
+        match robot {
+            Some(robot) => Ok(Warehouse {
+                map,
+                robot,
+                log: Vec::new(),
+            }),
+            None => bail!("There is no robot in the warehouse"),
+        }
+    }
+
+    pub fn robot(&self) -> Position {
+        self.robot
+    }
+
+    pub fn map(&self) -> &Grid<Tile> {
+        &self.map
+    }
+
+    /// Every cell of the boxes the last instruction pushed, where they are now.
+    pub fn moved(&self) -> Vec<Position> {
+        let Some(last) = self.log.last() else {
+            return Vec::new();
+        };
+        last.boxes
+            .iter()
+            .flat_map(|b| self.box_cells(move_direction(b, &last.direction)))
+            .collect()
+    }
+
+    /// Every instruction followed so far, in order.
+    pub fn log(&self) -> &[Move] {
+        &self.log
+    }
+
+    /// Sum of `100 * row + col` over the left (or only) cell of every box.
+    pub fn gps(&self) -> i32 {
+        self.map
+            .iter()
+            .filter(|(_, tile)| matches!(tile, Tile::Box | Tile::BoxLeft))
+            .map(|((r, c), _)| 100 * r + c)
+            .sum()
+    }
+
+    /// Move the robot one step, pushing any boxes in the way, unless something pushed would hit a
+    /// wall. Returns whether the robot moved.
+    pub fn step(&mut self, direction: Direction) -> bool {
+        let pushed = self.pushed_boxes(&direction);
+        let entry = Move {
+            robot: self.robot,
+            blocked: pushed.is_none(),
+            boxes: pushed.unwrap_or_default(),
+            direction,
+        };
+
+        if !entry.blocked {
+            self.shift(&entry.boxes, &entry.direction);
+            self.robot = move_direction(&self.robot, &entry.direction);
+        }
+        let moved = !entry.blocked;
+        self.log.push(entry);
+        moved
+    }
+
+    pub fn run(&mut self, directions: &[Direction]) {
+        for d in directions {
+            self.step(d.clone());
+        }
+    }
+
+    /// Take back the last instruction, returning how it played out.
+    pub fn undo(&mut self) -> Option<Move> {
+        let entry = self.log.pop()?;
+        if !entry.blocked {
+            let moved: Vec<Position> = entry
+                .boxes
+                .iter()
+                .map(|b| move_direction(b, &entry.direction))
+                .collect();
+            self.shift(&moved, &opposite_direction(&entry.direction));
+            self.robot = entry.robot;
+        }
+        Some(entry)
+    }
+
+    /// Undo or follow `directions` until exactly the first `steps` of them have been followed.
+    /// The log must already be a prefix of `directions`.
+    pub fn seek(&mut self, directions: &[Direction], steps: usize) -> Result<()> {
+        if steps > directions.len() {
+            bail!(
+                "Can't go to step {} of {} instructions",
+                steps,
+                directions.len()
+            );
+        }
+        while self.log.len() > steps {
+            self.undo();
+        }
+        self.run(&directions[self.log.len()..steps]);
+        Ok(())
+    }
+
+    /// The left (or only) cell of the box covering `pos`, if there is one.
+    fn box_at(&self, pos: Position) -> Option<Position> {
+        match self.map.get(pos)? {
+            Tile::Box | Tile::BoxLeft => Some(pos),
+            Tile::BoxRight => Some((pos.0, pos.1 - 1)),
+            Tile::Wall | Tile::Empty => None,
+        }
+    }
+
+    fn box_cells(&self, origin: Position) -> Vec<Position> {
+        match self.map[origin] {
+            Tile::BoxLeft => vec![origin, (origin.0, origin.1 + 1)],
+            _ => vec![origin],
+        }
+    }
+
+    /// Every box a step in `direction` would push, or `None` if any of them, or the robot, would
+    /// end up in a wall. A wide box pushed up or down pushes whatever is in front of either half.
+    fn pushed_boxes(&self, direction: &Direction) -> Option<Vec<Position>> {
+        let mut boxes = Vec::new();
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::from([move_direction(&self.robot, direction)]);
+
+        while let Some(pos) = queue.pop_front() {
+            match self.map.get(pos) {
+                None | Some(Tile::Wall) => return None,
+                Some(Tile::Empty) => continue,
+                _ => {}
+            }
+            let origin = self.box_at(pos).expect("Only boxes are left");
+            if !seen.insert(origin) {
+                continue;
+            }
+
+            let cells = self.box_cells(origin);
+            for cell in &cells {
+                let next = move_direction(cell, direction);
+                if !cells.contains(&next) {
+                    queue.push_back(next);
+                }
+            }
+            boxes.push(origin);
+        }
+
+        Some(boxes)
+    }
+
+    /// Move the boxes with these left cells one step, all at once.
+    fn shift(&mut self, boxes: &[Position], direction: &Direction) {
+        let tiles: Vec<(Position, Tile)> = boxes
+            .iter()
+            .flat_map(|b| self.box_cells(*b))
+            .map(|p| (p, self.map[p]))
+            .collect();
+        for (p, _) in &tiles {
+            self.map[*p] = Tile::Empty;
+        }
+        for (p, tile) in tiles {
+            self.map[move_direction(&p, direction)] = tile;
+        }
+    }
+}
+
+/// The map with the robot drawn as `@`.
+impl fmt::Display for Warehouse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut chars = self.map.map(|tile| tile.to_string());
+        chars[self.robot] = "@".to_string();
+        write!(f, "{}", chars)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn warehouse(layout: &str, wide: bool) -> Warehouse {
+        Warehouse::new(&Grid::parse_chars(layout).unwrap(), wide).unwrap()
+    }
+
+    fn arrows(moves: &str) -> Vec<Direction> {
+        moves.chars().filter_map(Direction::from_arrow).collect()
+    }
+
+    #[test]
+    fn pushes_rows_of_boxes() {
+        let mut w = warehouse("#######\n#@OO..#\n#######", false);
+        assert!(w.step(Direction::East));
+        assert_eq!(w.log()[0].boxes, [(1, 2), (1, 3)]);
+        assert!(w.step(Direction::East));
+        assert!(!w.step(Direction::East));
+        assert_eq!(w.to_string(), "#######\n#..@OO#\n#######");
+        assert!(w.log()[2].blocked);
+
+        w.undo();
+        w.undo();
+        assert_eq!(w.to_string(), "#######\n#.@OO.#\n#######");
+        assert_eq!(w.gps(), 103 + 104);
+    }
+
+    #[test]
+    fn pushes_chains_of_wide_boxes() {
+        // Shift the lower box half a box left, so pushing it up also pushes the one above
+        let start = "######\n#....#\n#.O..#\n#.O@.#\n#....#\n######";
+        let moves = arrows("<v<^");
+        let mut w = warehouse(start, true);
+        assert_eq!(
+            w.to_string(),
+            "############\n##........##\n##..[]....##\n##..[]@...##\n##........##\n############"
+        );
+
+        w.seek(&moves, 4).unwrap();
+        assert_eq!(w.log()[3].boxes, [(3, 3), (2, 4)]);
+        assert_eq!(
+            w.to_string(),
+            "############\n##..[]....##\n##.[].....##\n##..@.....##\n##........##\n############"
+        );
+        assert_eq!(w.gps(), 104 + 203);
+
+        w.seek(&moves, 1).unwrap();
+        assert_eq!(w.robot(), (3, 5));
+        w.seek(&moves, 0).unwrap();
+        assert_eq!(w.to_string(), warehouse(start, true).to_string());
+        assert!(w.seek(&moves, 5).is_err());
+    }
+
+    #[test]
+    fn walls_block_either_half() {
+        // Only the left half of the box runs into the wall, which is enough to stop it
+        let mut w = warehouse("######\n#....#\n##...#\n#.O@.#\n#....#\n######", true);
+        w.seek(&arrows("<v<"), 3).unwrap();
+        let before = w.to_string();
+        assert!(!w.step(Direction::North));
+        assert_eq!(w.to_string(), before);
+        assert_eq!(w.undo().map(|m| m.blocked), Some(true));
+
+        let mut w = warehouse("####\n#@O#\n####", true);
+        assert!(w.step(Direction::East));
+        assert!(!w.step(Direction::East));
+        assert!(w.step(Direction::West));
+    }
+}